@@ -1,21 +1,263 @@
 use crate::config::{ModuleConfig, RootModuleConfig, SegmentConfig};
+use std::collections::HashMap;
 
 use ansi_term::{Color, Style};
 use starship_module_config_derive::ModuleConfig;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VersionCase {
+    Preserve,
+    Lower,
+    Upper,
+}
+
+/// How much of the `git describe` output to show as the version
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DescribeFormat {
+    /// Only the tag, e.g. `v1.2.3`
+    Tag,
+    /// The tag and the number of commits since, e.g. `v1.2.3+5`
+    TagDistance,
+    /// The whole output, e.g. `v1.2.3-5-gabc1234`
+    Full,
+}
+
+/// Where the search for a package manifest starts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContextRoot {
+    CurrentDir,
+    GitRoot,
+}
+
+/// A file, and the dotted key its version is read from, if not one of the usual ones
+#[derive(Clone)]
+pub struct CustomManifest<'a> {
+    pub file: &'a str,
+    pub key: Option<&'a str>,
+}
+
+/// How a manifest registered in `detect_extensions` is parsed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ManifestParser {
+    Toml,
+    Json,
+    Yaml,
+    Regex,
+}
+
+/// Files with an extension, and the dotted key their version is read from, if not one of
+/// the usual ones, or for the `regex` parser the regex matching their version
+#[derive(Clone)]
+pub struct CustomExtensionManifest<'a> {
+    pub extension: &'a str,
+    pub parser: ManifestParser,
+    pub key: Option<&'a str>,
+}
+
+/// A file, and a regex whose `version` or first group matches its version
+#[derive(Clone, ModuleConfig)]
+pub struct CustomRegexManifest<'a> {
+    pub file: &'a str,
+    pub pattern: &'a str,
+}
+
 #[derive(Clone, ModuleConfig)]
 pub struct PackageConfig<'a> {
+    pub prefix: &'a str,
+    pub suffix: &'a str,
+    pub trailing_space: bool,
     pub symbol: SegmentConfig<'a>,
+    pub symbol_prefix: &'a str,
+    pub symbol_suffix: &'a str,
+    pub symbol_separator: &'a str,
+    pub show_ecosystem_label: bool,
+    pub symbol_variants: HashMap<String, &'a str>,
     pub style: Style,
+    pub display_name: bool,
+    pub truncate_name_length: Option<usize>,
+    pub show_parse_errors: bool,
+    pub error_symbol: &'a str,
+    pub error_style: Option<Style>,
+    pub unstable_style: Option<Style>,
+    pub show_when_no_version: bool,
+    pub context_root: ContextRoot,
+    pub max_search_depth: usize,
+    pub ignore_paths: Vec<&'a str>,
+    pub extra_yaml_manifests: Vec<&'a str>,
+    pub version_file_names: Vec<&'a str>,
+    pub detect_toml: Vec<CustomManifest<'a>>,
+    pub detect_json: Vec<CustomManifest<'a>>,
+    pub detect_yaml: Vec<CustomManifest<'a>>,
+    pub detect_regex: Vec<CustomRegexManifest<'a>>,
+    pub detect_extensions: Vec<CustomExtensionManifest<'a>>,
+    pub placeholder_versions: Vec<&'a str>,
+    pub resolve_gem_version_constant: bool,
+    pub resolve_gemfile_lock: bool,
+    pub resolve_git_version: bool,
+    pub resolve_opam_version: bool,
+    pub require_clean_repo: bool,
+    pub describe_format: DescribeFormat,
+    pub hide_unpublishable: bool,
+    pub when: Option<&'a str>,
+    pub version_command: Option<&'a str>,
+    pub readme_badge_fallback: bool,
+    pub fallback_version: Option<&'a str>,
+    pub trim_prefix: Option<&'a str>,
+    pub hide_calver_prefix: bool,
+    pub strip_debian_revision: bool,
+    pub version_map: HashMap<String, &'a str>,
+    pub warn_on_version_mismatch: bool,
+    pub warn_on_lock_mismatch: bool,
+    pub mismatch_symbol: &'a str,
+    pub version_case: VersionCase,
+    pub show_tool_version: bool,
+    pub show_elm_version: bool,
+    pub link_to_registry: bool,
+    pub registry_overrides: HashMap<String, &'a str>,
+    pub check_updates: bool,
+    pub update_symbol: &'a str,
+    pub cache_ttl_ms: u64,
     pub disabled: bool,
 }
 
 impl<'a> RootModuleConfig<'a> for PackageConfig<'a> {
     fn new() -> Self {
         PackageConfig {
+            prefix: "is ",
+            suffix: " ",
+            trailing_space: true,
             symbol: SegmentConfig::new("📦 "),
+            symbol_prefix: "",
+            symbol_suffix: "",
+            symbol_separator: "",
+            show_ecosystem_label: false,
+            symbol_variants: HashMap::new(),
             style: Color::Fixed(208).bold(),
+            display_name: false,
+            truncate_name_length: None,
+            show_parse_errors: false,
+            error_symbol: "",
+            error_style: None,
+            unstable_style: None,
+            show_when_no_version: false,
+            context_root: ContextRoot::CurrentDir,
+            max_search_depth: 8,
+            ignore_paths: vec![],
+            extra_yaml_manifests: vec![],
+            version_file_names: vec!["VERSION", "version.txt"],
+            detect_toml: vec![],
+            detect_json: vec![],
+            detect_yaml: vec![],
+            detect_regex: vec![],
+            detect_extensions: vec![],
+            placeholder_versions: vec!["0.0.0"],
+            resolve_gem_version_constant: false,
+            resolve_gemfile_lock: false,
+            resolve_git_version: false,
+            resolve_opam_version: false,
+            require_clean_repo: false,
+            describe_format: DescribeFormat::Full,
+            hide_unpublishable: false,
+            when: None,
+            version_command: None,
+            readme_badge_fallback: false,
+            fallback_version: None,
+            trim_prefix: None,
+            hide_calver_prefix: false,
+            strip_debian_revision: false,
+            version_map: HashMap::new(),
+            warn_on_version_mismatch: false,
+            warn_on_lock_mismatch: false,
+            mismatch_symbol: "!",
+            version_case: VersionCase::Preserve,
+            show_tool_version: false,
+            show_elm_version: false,
+            link_to_registry: false,
+            registry_overrides: HashMap::new(),
+            check_updates: false,
+            update_symbol: "⇡",
+            cache_ttl_ms: 6 * 60 * 60 * 1000,
             disabled: false,
         }
     }
 }
+
+impl<'a> ModuleConfig<'a> for VersionCase {
+    fn from_config(config: &toml::Value) -> Option<Self> {
+        match config.as_str()? {
+            "preserve" => Some(VersionCase::Preserve),
+            "lower" => Some(VersionCase::Lower),
+            "upper" => Some(VersionCase::Upper),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> ModuleConfig<'a> for DescribeFormat {
+    fn from_config(config: &toml::Value) -> Option<Self> {
+        match config.as_str()? {
+            "tag" => Some(DescribeFormat::Tag),
+            "tag_distance" => Some(DescribeFormat::TagDistance),
+            "full" => Some(DescribeFormat::Full),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> ModuleConfig<'a> for ContextRoot {
+    fn from_config(config: &toml::Value) -> Option<Self> {
+        match config.as_str()? {
+            "current_dir" => Some(ContextRoot::CurrentDir),
+            "git_root" => Some(ContextRoot::GitRoot),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> ModuleConfig<'a> for CustomManifest<'a> {
+    fn from_config(config: &'a toml::Value) -> Option<Self> {
+        match config {
+            toml::Value::String(file) => Some(CustomManifest { file, key: None }),
+            toml::Value::Table(manifest) => Some(CustomManifest {
+                file: manifest.get("file")?.as_str()?,
+                key: match manifest.get("key") {
+                    Some(key) => Some(key.as_str()?),
+                    None => None,
+                },
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> ModuleConfig<'a> for ManifestParser {
+    fn from_config(config: &toml::Value) -> Option<Self> {
+        match config.as_str()? {
+            "toml" => Some(ManifestParser::Toml),
+            "json" => Some(ManifestParser::Json),
+            "yaml" => Some(ManifestParser::Yaml),
+            "regex" => Some(ManifestParser::Regex),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> ModuleConfig<'a> for CustomExtensionManifest<'a> {
+    fn from_config(config: &'a toml::Value) -> Option<Self> {
+        let manifest = config.as_table()?;
+        let extension = manifest.get("extension")?.as_str()?;
+        let parser = ManifestParser::from_config(manifest.get("parser")?)?;
+        let key = match manifest.get("key") {
+            Some(key) => Some(key.as_str()?),
+            // There is no usual regex to fall back to
+            None if parser == ManifestParser::Regex => return None,
+            None => None,
+        };
+
+        Some(CustomExtensionManifest {
+            extension: extension.trim_start_matches('.'),
+            parser,
+            key,
+        })
+    }
+}
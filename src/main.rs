@@ -128,6 +128,18 @@ fn main() {
             .subcommand(
                 SubCommand::with_name("explain").about("Explains the currently showing modules"),
             )
+            .subcommand(
+                SubCommand::with_name(modules::package::WARM_CACHE_SUBCOMMAND)
+                    .about("Resolves the current package version ahead of the prompt")
+                    .arg(&path_arg)
+                    .settings(&[AppSettings::Hidden]),
+            )
+            .subcommand(
+                SubCommand::with_name(modules::package::UPDATE_CHECK_SUBCOMMAND)
+                    .about("Checks the latest release of the current package in the background")
+                    .arg(&path_arg)
+                    .settings(&[AppSettings::Hidden]),
+            )
             .get_matches();
 
     match matches.subcommand() {
@@ -164,6 +176,12 @@ fn main() {
             }
         }
         ("explain", Some(sub_m)) => print::explain(sub_m.clone()),
+        (modules::package::WARM_CACHE_SUBCOMMAND, Some(sub_m)) => {
+            modules::package::warm_package_cache(&context::Context::new(sub_m.clone()));
+        }
+        (modules::package::UPDATE_CHECK_SUBCOMMAND, Some(sub_m)) => {
+            modules::package::check_for_update(&context::Context::new(sub_m.clone()))
+        }
         _ => {}
     }
 }
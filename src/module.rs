@@ -114,6 +114,11 @@ impl<'a> Module<'a> {
         &self.description
     }
 
+    /// Replace the module's description, e.g. to explain where its value comes from
+    pub fn set_description(&mut self, description: String) {
+        self.description = description;
+    }
+
     /// Whether a module has non-empty segments
     pub fn is_empty(&self) -> bool {
         self.segments.iter().all(|segment| segment.is_empty())
@@ -1,5 +1,6 @@
 use ansi_term::Color;
 use std::io::Write;
+use std::path::Path;
 use std::process::{Command, Output, Stdio};
 
 use super::{Context, Module, RootModuleConfig};
@@ -57,7 +58,7 @@ pub fn module<'a>(name: &'a str, context: &'a Context) -> Option<Module<'a>> {
         module.create_segment("symbol", &symbol);
     }
 
-    if let Some(output) = exec_command(config.command, config.shell) {
+    if let Some(output) = exec_command(config.command, config.shell, None) {
         let trimmed = output.trim();
 
         if trimmed.is_empty() {
@@ -87,10 +88,11 @@ fn get_shell(shell: Option<&str>) -> std::borrow::Cow<str> {
     }
 }
 
-/// Attempt to run the given command in a shell by passing it as `stdin` to `get_shell()`
+/// Attempt to run the given command in a shell by passing it as `stdin` to `get_shell()`,
+/// from within `dir` if given
 #[cfg(not(windows))]
-fn shell_command(cmd: &str, shell: Option<&str>) -> Option<Output> {
-    let command = Command::new(get_shell(shell).as_ref())
+fn shell_command(cmd: &str, shell: Option<&str>, dir: Option<&Path>) -> Option<Output> {
+    let command = in_dir(Command::new(get_shell(shell).as_ref()), dir)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -103,7 +105,7 @@ fn shell_command(cmd: &str, shell: Option<&str>) -> Option<Output> {
                 "Could not launch command with given shell or STARSHIP_SHELL env variable, retrying with /bin/env sh"
             );
 
-            Command::new("/bin/env")
+            in_dir(Command::new("/bin/env"), dir)
                 .arg("sh")
                 .stdin(Stdio::piped())
                 .stdout(Stdio::piped())
@@ -118,9 +120,9 @@ fn shell_command(cmd: &str, shell: Option<&str>) -> Option<Output> {
 }
 
 /// Attempt to run the given command in a shell by passing it as `stdin` to `get_shell()`,
-/// or by invoking cmd.exe /C.
+/// or by invoking cmd.exe /C, from within `dir` if given.
 #[cfg(windows)]
-fn shell_command(cmd: &str, shell: Option<&str>) -> Option<Output> {
+fn shell_command(cmd: &str, shell: Option<&str>, dir: Option<&Path>) -> Option<Output> {
     let shell = if let Some(shell) = shell {
         Some(std::borrow::Cow::Borrowed(shell))
     } else if let Ok(env_shell) = std::env::var("STARSHIP_SHELL") {
@@ -130,7 +132,7 @@ fn shell_command(cmd: &str, shell: Option<&str>) -> Option<Output> {
     };
 
     if let Some(forced_shell) = shell {
-        let command = Command::new(forced_shell.as_ref())
+        let command = in_dir(Command::new(forced_shell.as_ref()), dir)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -147,7 +149,7 @@ fn shell_command(cmd: &str, shell: Option<&str>) -> Option<Output> {
         );
     }
 
-    let command = Command::new("cmd.exe")
+    let command = in_dir(Command::new("cmd.exe"), dir)
         .arg("/C")
         .arg(cmd)
         .stdin(Stdio::piped())
@@ -158,11 +160,19 @@ fn shell_command(cmd: &str, shell: Option<&str>) -> Option<Output> {
     command.ok()?.wait_with_output().ok()
 }
 
+/// Run the command from within `dir`, or the current directory if `None`
+fn in_dir(mut command: Command, dir: Option<&Path>) -> Command {
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    command
+}
+
 /// Execute the given command capturing all output, and return whether it return 0
-fn exec_when(cmd: &str, shell: Option<&str>) -> bool {
+pub(crate) fn exec_when(cmd: &str, shell: Option<&str>) -> bool {
     log::trace!("Running '{}'", cmd);
 
-    if let Some(output) = shell_command(cmd, shell) {
+    if let Some(output) = shell_command(cmd, shell, None) {
         if !output.status.success() {
             log::trace!("non-zero exit code '{:?}'", output.status.code());
             log::trace!(
@@ -184,10 +194,10 @@ fn exec_when(cmd: &str, shell: Option<&str>) -> bool {
 }
 
 /// Execute the given command, returning its output on success
-fn exec_command(cmd: &str, shell: Option<&str>) -> Option<String> {
+pub(crate) fn exec_command(cmd: &str, shell: Option<&str>, dir: Option<&Path>) -> Option<String> {
     log::trace!("Running '{}'", cmd);
 
-    if let Some(output) = shell_command(cmd, shell) {
+    if let Some(output) = shell_command(cmd, shell, dir) {
         if !output.status.success() {
             log::trace!("Non-zero exit code '{:?}'", output.status.code());
             log::trace!(
@@ -237,9 +247,12 @@ mod tests {
     #[test]
     #[cfg(not(windows))]
     fn command_returns_right_string() {
-        assert_eq!(exec_command("echo hello", SHELL), Some("hello\n".into()));
         assert_eq!(
-            exec_command("echo 강남스타일", SHELL),
+            exec_command("echo hello", SHELL, None),
+            Some("hello\n".into())
+        );
+        assert_eq!(
+            exec_command("echo 강남스타일", SHELL, None),
             Some("강남스타일\n".into())
         );
     }
@@ -247,9 +260,12 @@ mod tests {
     #[test]
     #[cfg(windows)]
     fn command_returns_right_string() {
-        assert_eq!(exec_command("echo hello", SHELL), Some("hello\r\n".into()));
         assert_eq!(
-            exec_command("echo 강남스타일", SHELL),
+            exec_command("echo hello", SHELL, None),
+            Some("hello\r\n".into())
+        );
+        assert_eq!(
+            exec_command("echo 강남스타일", SHELL, None),
             Some("강남스타일\r\n".into())
         );
     }
@@ -258,11 +274,11 @@ mod tests {
     #[cfg(not(windows))]
     fn command_ignores_stderr() {
         assert_eq!(
-            exec_command("echo foo 1>&2; echo bar", SHELL),
+            exec_command("echo foo 1>&2; echo bar", SHELL, None),
             Some("bar\n".into())
         );
         assert_eq!(
-            exec_command("echo foo; echo bar 1>&2", SHELL),
+            exec_command("echo foo; echo bar 1>&2", SHELL, None),
             Some("foo\n".into())
         );
     }
@@ -271,18 +287,18 @@ mod tests {
     #[cfg(windows)]
     fn command_ignores_stderr() {
         assert_eq!(
-            exec_command("echo foo 1>&2 & echo bar", SHELL),
+            exec_command("echo foo 1>&2 & echo bar", SHELL, None),
             Some("bar\r\n".into())
         );
         assert_eq!(
-            exec_command("echo foo& echo bar 1>&2", SHELL),
+            exec_command("echo foo& echo bar 1>&2", SHELL, None),
             Some("foo\r\n".into())
         );
     }
 
     #[test]
     fn command_can_fail() {
-        assert_eq!(exec_command(FAILING_COMMAND, SHELL), None);
-        assert_eq!(exec_command(UNKNOWN_COMMAND, SHELL), None);
+        assert_eq!(exec_command(FAILING_COMMAND, SHELL, None), None);
+        assert_eq!(exec_command(UNKNOWN_COMMAND, SHELL, None), None);
     }
 }
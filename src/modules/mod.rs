@@ -27,7 +27,7 @@ mod line_break;
 mod memory_usage;
 mod nix_shell;
 mod nodejs;
-mod package;
+pub mod package;
 mod php;
 mod python;
 mod ruby;
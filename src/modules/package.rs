@@ -1,457 +1,8866 @@
-use std::path::PathBuf;
+use std::cell::OnceCell;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime};
 
-use super::{Context, Module};
+use super::{custom, Context, Module};
 use crate::utils;
 
+use once_cell::sync::Lazy;
 use regex::Regex;
 use serde_json as json;
 use toml;
+use yaml_rust::{Yaml, YamlLoader};
 
 use super::{RootModuleConfig, SegmentConfig};
-use crate::configs::package::PackageConfig;
+use crate::configs::package::{
+    ContextRoot, CustomManifest, DescribeFormat, ManifestParser, PackageConfig, VersionCase,
+};
 
 /// Creates a module with the current package version
 ///
 /// Will display if a version is defined for your Node.js or Rust project (if one exists)
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    match get_package_version(&context.current_dir) {
-        Some(package_version) => {
-            let mut module = context.new_module("package");
-            let config: PackageConfig = PackageConfig::try_load(module.config);
+    let mut module = context.new_module("package");
+    let config: PackageConfig = PackageConfig::try_load(module.config);
 
+    if is_ignored_path(&context.current_dir, &config.ignore_paths) {
+        log::debug!("Package module disabled in ignored path");
+        return None;
+    }
+
+    let passes_when = || config.when.is_none_or(|when| custom::exec_when(when, None));
+    let lookup = find_package_version(context, &config);
+    // Checked before anything is shown, parse errors and versionless manifests included
+    let is_shown = match &lookup {
+        Ok(_) => true,
+        Err(PackageError::ParseFailed) => config.show_parse_errors,
+        Err(PackageError::NoVersionField) => config.show_when_no_version,
+        Err(_) => false,
+    };
+    if is_shown && !passes_when() {
+        return None;
+    }
+
+    let (mut version, dir, package) = match lookup {
+        Ok(package_version) => (
+            package_version.version.clone(),
+            package_version.dir.clone(),
+            Some(package_version),
+        ),
+        Err(PackageError::NotFound) => {
+            let version = get_fallback_version(&context.current_dir, &config)?;
+            if !passes_when() {
+                return None;
+            }
+            (version, context.current_dir.clone(), None)
+        }
+        Err(PackageError::ParseFailed) if config.show_parse_errors => {
+            log::debug!("Unable to parse package manifest");
             module.set_style(config.style);
-            module.get_prefix().set_value("is ");
+            module.get_prefix().set_value(config.prefix);
+            module.get_suffix().set_value(get_suffix(&config));
+            let symbol = pad_symbol(config.symbol.value, &config);
+            module.create_segment("symbol", &config.symbol.with_value(&symbol));
+            module.create_segment(
+                "error_symbol",
+                &SegmentConfig {
+                    value: config.error_symbol,
+                    style: config.error_style,
+                },
+            );
+            return Some(module);
+        }
+        Err(PackageError::NoVersionField) if config.show_when_no_version => {
+            log::debug!("Package manifest found without a version");
+            module.set_style(config.style);
+            module.get_prefix().set_value(config.prefix);
+            module.get_suffix().set_value(get_suffix(&config));
+            // The symbol's trailing space separates it from a version that isn't there
+            let symbol = pad_symbol(config.symbol.value.trim_end(), &config);
+            module.create_segment("symbol", &config.symbol.with_value(&symbol));
+            return Some(module);
+        }
+        Err(error) => {
+            log::debug!("Unable to get package version: {:?}", error);
+            return None;
+        }
+    };
+
+    let manifest = package.as_ref().map(|package| package.file.as_path());
+    version = resolve_placeholder_version(version, &dir, manifest, &config)?;
+    let resolved_version = version.clone();
+
+    if let Some(mapped) = map_version(&version, &config.version_map) {
+        version = mapped.to_string();
+    } else {
+        if let Some(prefix) = config.trim_prefix {
+            version = trim_version_prefix(&version, prefix);
+        }
+        if config.hide_calver_prefix {
+            version = trim_calver_prefix(&version);
+        }
+        version = apply_version_case(&version, config.version_case);
+    }
+
+    if let Some(package) = &package {
+        module.set_description(explain_package_version(
+            package,
+            &resolved_version,
+            &version,
+            &config,
+        ));
+    }
+
+    module.set_style(config.style);
+    module.get_prefix().set_value(config.prefix);
+    module.get_suffix().set_value(get_suffix(&config));
+
+    let registry_overrides = &config.registry_overrides;
+    let registry_override = |manifest: ManifestKind| {
+        let ecosystem = manifest.ecosystem()?;
+        registry_overrides.get(ecosystem).copied()
+    };
+
+    let symbol = get_symbol_variant(&dir, &config)
+        .map(|variant| config.symbol.with_value(variant))
+        .unwrap_or_else(|| config.symbol.clone());
+
+    let padded_symbol = pad_symbol(symbol.value, &config);
+    module.create_segment("symbol", &symbol.with_value(&padded_symbol));
+    if !config.symbol_separator.is_empty() {
+        module.create_segment(
+            "symbol_separator",
+            &SegmentConfig::new(config.symbol_separator),
+        );
+    }
+
+    if config.show_ecosystem_label {
+        let label = package
+            .as_ref()
+            .and_then(|package| package.manifest.label());
+        if let Some(label) = label {
+            module.create_segment("ecosystem", &SegmentConfig::new(&format!("{} ", label)));
+        }
+    }
+
+    if config.display_name {
+        let name = package
+            .as_ref()
+            .and_then(|package| Some(package.manifest.display_name(package.name.as_deref()?)));
+        if let Some(name) = name {
+            let name = match config.truncate_name_length {
+                Some(max_length) => truncate_name(name, max_length),
+                None => name.to_string(),
+            };
+            module.create_segment("name", &SegmentConfig::new(&format!("{} ", name)));
+        }
+    }
+
+    let version_style = config
+        .unstable_style
+        .filter(|_| is_unstable_version(&version));
+
+    if config.link_to_registry {
+        let registry_url = package.as_ref().and_then(|package| {
+            let name = package.name.as_deref()?;
+            package
+                .manifest
+                .registry_url(name, registry_override(package.manifest))
+        });
+        if let Some(url) = registry_url {
+            version = format_hyperlink(&url, &version);
+        }
+    }
+    module.create_segment(
+        "version",
+        &SegmentConfig {
+            value: &version,
+            style: version_style,
+        },
+    );
+
+    if let Some(package) = &package {
+        let mut mismatched = false;
+        if config.warn_on_version_mismatch {
+            let mismatches = find_version_mismatches(package, &config);
+            if !mismatches.is_empty() {
+                log::warn!(
+                    "Package version {} doesn't match the other manifests: {}",
+                    package.version,
+                    mismatches.join(", ")
+                );
+                mismatched = true;
+            }
+        }
+        if config.warn_on_lock_mismatch {
+            let locked_version = get_locked_version(&package.dir);
+            if let Some(locked_version) = locked_version.filter(|locked| *locked != package.version)
+            {
+                log::warn!(
+                    "Package version {} doesn't match the version {} pinned in {}",
+                    package.version,
+                    locked_version,
+                    VERSION_LOCK_FILE
+                );
+                mismatched = true;
+            }
+        }
+        if mismatched {
+            module.create_segment("mismatch", &SegmentConfig::new(config.mismatch_symbol));
+        }
+    }
 
-            module.create_segment("symbol", &config.symbol);
-            module.create_segment("version", &SegmentConfig::new(&package_version));
+    if config.check_updates {
+        let latest_version = package.as_ref().and_then(|package| {
+            let url = get_latest_version_url(package, &config)?;
+            let latest_version = get_latest_version(
+                &url,
+                &package_cache_dir()?,
+                Duration::from_millis(config.cache_ttl_ms),
+                SystemTime::now(),
+                || start_update_check(&context.current_dir),
+            )?;
+            Some(latest_version).filter(|latest| is_version_behind(&package.version, latest))
+        });
+        if let Some(latest_version) = latest_version {
+            module.create_segment(
+                "update",
+                &SegmentConfig::new(&format!(" {}{}", config.update_symbol, latest_version)),
+            );
+        }
+    }
 
-            Some(module)
+    if config.show_tool_version {
+        let tool_version = package
+            .as_ref()
+            .and_then(|package| get_tool_version(package.manifest, &dir));
+        if let Some(tool_version) = tool_version {
+            module.create_segment(
+                "tool_version",
+                &SegmentConfig::new(&format!(" ({})", tool_version)),
+            );
         }
-        None => None,
     }
+
+    Some(module)
 }
 
-fn extract_cargo_version(file_contents: &str) -> Option<String> {
-    let cargo_toml: toml::Value = toml::from_str(file_contents).ok()?;
-    let raw_version = cargo_toml.get("package")?.get("version")?.as_str()?;
+/// The suffix of the module, without its trailing whitespace unless `trailing_space` is set
+fn get_suffix<'a>(config: &PackageConfig<'a>) -> &'a str {
+    if config.trailing_space {
+        config.suffix
+    } else {
+        config.suffix.trim_end()
+    }
+}
+
+/// The version of the package found in a directory
+#[derive(Debug, PartialEq)]
+struct PackageVersion {
+    /// The formatted version, ready to be displayed
+    version: String,
+    /// The kind of manifest the version was read from
+    manifest: ManifestKind,
+    /// The name of the package, if the manifest declares one
+    name: Option<String>,
+    /// The directory containing the manifest the version was read from
+    dir: PathBuf,
+    /// The manifest the version was read from
+    file: PathBuf,
+}
+
+/// The manifests a package version can be read from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ManifestKind {
+    Cargo,
+    Npm,
+    Deno,
+    PyProject,
+    Composer,
+    Gradle,
+    Maven,
+    Ant,
+    Julia,
+    Gleam,
+    Mix,
+    Vcpkg,
+    Racket,
+    WebExtension,
+    GrafanaPlugin,
+    Zig,
+    CocoaPods,
+    Gemspec,
+    Chef,
+    Homebrew,
+    Dune,
+    Opam,
+    Elm,
+    Hpack,
+    Cabal,
+    CabalProject,
+    Pubspec,
+    Dotnet,
+    Nuspec,
+    Plist,
+    Swift,
+    BazelModule,
+    Bazel,
+    RpmSpec,
+    DebianChangelog,
+    Rockspec,
+    Sfdx,
+    CitationCff,
+    Dockerfile,
+    /// One of the `extra_yaml_manifests` configured by the user
+    Yaml,
+    /// One of the `detect_*` manifests configured by the user
+    Custom,
+    /// One of the `version_file_names` holding just a version, like `VERSION`
+    VersionFile,
+}
+
+/// The manifests probed by `get_package_version`, in order of priority
+const SUPPORTED_MANIFESTS: &[ManifestKind] = &[
+    // maturin builds Python packages from the version in `Cargo.toml` unless `pyproject.toml`
+    // sets one of its own
+    ManifestKind::Cargo,
+    ManifestKind::Npm,
+    ManifestKind::Deno,
+    ManifestKind::PyProject,
+    ManifestKind::Composer,
+    ManifestKind::Gradle,
+    ManifestKind::Maven,
+    ManifestKind::Ant,
+    ManifestKind::Julia,
+    ManifestKind::Gleam,
+    ManifestKind::Mix,
+    ManifestKind::Vcpkg,
+    ManifestKind::Racket,
+    ManifestKind::WebExtension,
+    ManifestKind::GrafanaPlugin,
+    ManifestKind::Zig,
+    ManifestKind::CocoaPods,
+    ManifestKind::Gemspec,
+    ManifestKind::Chef,
+    ManifestKind::Homebrew,
+    ManifestKind::Dune,
+    ManifestKind::Opam,
+    ManifestKind::Elm,
+    ManifestKind::Hpack,
+    ManifestKind::Cabal,
+    ManifestKind::CabalProject,
+    ManifestKind::Pubspec,
+    ManifestKind::Dotnet,
+    ManifestKind::Nuspec,
+    ManifestKind::Plist,
+    ManifestKind::Swift,
+    ManifestKind::BazelModule,
+    ManifestKind::Bazel,
+    ManifestKind::RpmSpec,
+    ManifestKind::DebianChangelog,
+    ManifestKind::Rockspec,
+    ManifestKind::Sfdx,
+    ManifestKind::CitationCff,
+    ManifestKind::Dockerfile,
+];
+
+/// The manifests the package module recognizes, in the order they are probed
+pub(crate) fn supported_manifests() -> &'static [ManifestKind] {
+    SUPPORTED_MANIFESTS
+}
+
+/// How a manifest is located within a directory
+enum ManifestFile {
+    /// A file with exactly this name
+    Name(&'static str),
+    /// The first of these files that exists
+    AnyName(&'static [&'static str]),
+    /// The first file with this extension
+    Extension(&'static str),
+    /// The first file with this extension, in the first of these subdirectories containing one
+    ExtensionIn(&'static [&'static str], &'static str),
+    /// The first file whose name matches this glob pattern
+    Pattern(&'static str),
+    /// The file with this extension whose name ends with the highest `<version>-<revision>`
+    NewestExtension(&'static str),
+}
+
+impl ManifestKind {
+    fn file(self) -> ManifestFile {
+        match self {
+            ManifestKind::Cargo => ManifestFile::Name("Cargo.toml"),
+            ManifestKind::Npm => ManifestFile::Name("package.json"),
+            ManifestKind::Deno => ManifestFile::AnyName(&["deno.json", "deno.jsonc"]),
+            ManifestKind::PyProject => ManifestFile::Name("pyproject.toml"),
+            ManifestKind::Composer => ManifestFile::Name("composer.json"),
+            ManifestKind::Gradle => ManifestFile::AnyName(&["build.gradle", "build.gradle.kts"]),
+            ManifestKind::Maven => ManifestFile::Name("pom.xml"),
+            ManifestKind::Ant => ManifestFile::Name("build.xml"),
+            ManifestKind::Julia => ManifestFile::Name("Project.toml"),
+            ManifestKind::Gleam => ManifestFile::Name("gleam.toml"),
+            ManifestKind::Mix => ManifestFile::Name("mix.exs"),
+            ManifestKind::Vcpkg => ManifestFile::Name("vcpkg.json"),
+            ManifestKind::Racket => ManifestFile::Name("info.rkt"),
+            ManifestKind::WebExtension => ManifestFile::Name("manifest.json"),
+            ManifestKind::GrafanaPlugin => ManifestFile::Name("plugin.json"),
+            ManifestKind::Zig => ManifestFile::Name("build.zig"),
+            ManifestKind::CocoaPods => ManifestFile::Extension("podspec"),
+            ManifestKind::Gemspec => ManifestFile::Extension("gemspec"),
+            ManifestKind::Chef => ManifestFile::Name("metadata.rb"),
+            ManifestKind::Homebrew => ManifestFile::ExtensionIn(&["", "Formula"], "rb"),
+            ManifestKind::Dune => ManifestFile::Name("dune-project"),
+            ManifestKind::Opam => ManifestFile::Extension("opam"),
+            // `elm-package.json` was replaced by `elm.json` in Elm 0.19
+            ManifestKind::Elm => ManifestFile::AnyName(&["elm.json", "elm-package.json"]),
+            // hpack generates the `.cabal` file from `package.yaml`, so it's probed first
+            ManifestKind::Hpack => ManifestFile::Name("package.yaml"),
+            ManifestKind::Cabal => ManifestFile::Extension("cabal"),
+            ManifestKind::CabalProject => ManifestFile::Name("cabal.project"),
+            ManifestKind::Pubspec => ManifestFile::Name("pubspec.yaml"),
+            ManifestKind::Dotnet => ManifestFile::Extension("csproj"),
+            ManifestKind::Nuspec => ManifestFile::Extension("nuspec"),
+            // Only XML property lists are supported, binary ones can't be read as text
+            ManifestKind::Plist => ManifestFile::Name("Info.plist"),
+            // Like `Package.swift`, or `Package@swift-5.9.swift` for a specific Swift version
+            ManifestKind::Swift => ManifestFile::Pattern("Package*.swift"),
+            ManifestKind::BazelModule => ManifestFile::Name("MODULE.bazel"),
+            // Only consulted for packaging rules when there is no `MODULE.bazel`
+            ManifestKind::Bazel => ManifestFile::AnyName(&["BUILD.bazel", "BUILD"]),
+            ManifestKind::RpmSpec => ManifestFile::Extension("spec"),
+            ManifestKind::DebianChangelog => ManifestFile::Name("debian/changelog"),
+            ManifestKind::Rockspec => ManifestFile::NewestExtension("rockspec"),
+            ManifestKind::Sfdx => ManifestFile::Name("sfdx-project.json"),
+            ManifestKind::CitationCff => ManifestFile::Name("CITATION.cff"),
+            ManifestKind::Dockerfile => ManifestFile::Name("Dockerfile"),
+            // The file names are configured, see `get_package_version`
+            ManifestKind::Yaml | ManifestKind::Custom | ManifestKind::VersionFile => {
+                ManifestFile::AnyName(&[])
+            }
+        }
+    }
+
+    /// The path of this manifest among the `files` of a directory, if there is one
+    fn locate(self, files: &DirFiles) -> Result<Option<PathBuf>, PackageError> {
+        let base_dir = files.dir;
+        match self.file() {
+            ManifestFile::Name(file_name) => Ok(find_manifest(base_dir, file_name)),
+            ManifestFile::AnyName(file_names) => Ok(file_names
+                .iter()
+                .find_map(|file_name| find_manifest(base_dir, file_name))),
+            ManifestFile::Extension(extension) => find_manifest_with_extension(files, extension),
+            ManifestFile::Pattern(pattern) => find_manifest_matching(files, pattern),
+            ManifestFile::NewestExtension(extension) => {
+                find_newest_manifest_with_extension(files, extension)
+            }
+            ManifestFile::ExtensionIn(dir_names, extension) => {
+                for dir_name in dir_names {
+                    let manifest = if dir_name.is_empty() {
+                        find_manifest_with_extension(files, extension)?
+                    } else {
+                        let dir = base_dir.join(dir_name);
+                        if !dir.is_dir() {
+                            continue;
+                        }
+                        find_manifest_with_extension(&DirFiles::new(&dir), extension)?
+                    };
+                    if manifest.is_some() {
+                        return Ok(manifest);
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Read this manifest from `base_dir`, returning `None` if it doesn't exist
+    fn read(self, base_dir: &Path) -> Result<Option<String>, PackageError> {
+        match self.locate(&DirFiles::new(base_dir))? {
+            Some(manifest) => Ok(Some(utils::read_file(manifest)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The name of the package declared in this manifest, if any
+    fn extract_name(self, file_contents: &str) -> Option<String> {
+        let name = match self {
+            ManifestKind::Cargo => {
+                let cargo_toml: toml::Value = toml::from_str(file_contents).ok()?;
+                cargo_toml
+                    .get("package")?
+                    .get("name")?
+                    .as_str()?
+                    .to_string()
+            }
+            ManifestKind::Npm | ManifestKind::Composer => {
+                let package_json: json::Value = json::from_str(file_contents).ok()?;
+                package_json.get("name")?.as_str()?.to_string()
+            }
+            ManifestKind::PyProject => {
+                let pyproject_toml: toml::Value = toml::from_str(file_contents).ok()?;
+                pyproject_toml
+                    .get("project")
+                    .or_else(|| pyproject_toml.get("tool")?.get("poetry"))?
+                    .get("name")?
+                    .as_str()?
+                    .to_string()
+            }
+            _ => return None,
+        };
+        // Names are shown in the prompt too, so they mustn't carry escape sequences either
+        Some(sanitize_version(&name))
+    }
+
+    /// The part of a package name worth showing in the prompt
+    fn display_name(self, name: &str) -> &str {
+        match self {
+            // The vendor of `vendor/package` is usually the name of its author
+            ManifestKind::Composer => name.rsplit('/').next().unwrap_or(name),
+            _ => name,
+        }
+    }
+
+    /// The key of the ecosystem of this manifest in `registry_overrides`
+    fn ecosystem(self) -> Option<&'static str> {
+        match self {
+            ManifestKind::Cargo => Some("cargo"),
+            ManifestKind::Npm => Some("npm"),
+            ManifestKind::PyProject => Some("python"),
+            ManifestKind::Composer => Some("composer"),
+            _ => None,
+        }
+    }
+
+    /// The short name of the ecosystem shown by `show_ecosystem_label`, e.g. `npm`
+    fn label(self) -> Option<&'static str> {
+        let label = match self {
+            ManifestKind::Cargo => "cargo",
+            ManifestKind::Npm => "npm",
+            ManifestKind::Deno => "deno",
+            ManifestKind::PyProject => "pypi",
+            ManifestKind::Composer => "composer",
+            ManifestKind::Gradle => "gradle",
+            ManifestKind::Maven => "maven",
+            ManifestKind::Ant => "ant",
+            ManifestKind::Julia => "julia",
+            ManifestKind::Gleam => "gleam",
+            ManifestKind::Mix => "hex",
+            ManifestKind::Vcpkg => "vcpkg",
+            ManifestKind::Racket => "raco",
+            ManifestKind::WebExtension => "webext",
+            ManifestKind::GrafanaPlugin => "grafana",
+            ManifestKind::Zig => "zig",
+            ManifestKind::CocoaPods => "pod",
+            ManifestKind::Gemspec => "gem",
+            ManifestKind::Chef => "chef",
+            ManifestKind::Homebrew => "brew",
+            ManifestKind::Dune | ManifestKind::Opam => "opam",
+            ManifestKind::Elm => "elm",
+            ManifestKind::Hpack | ManifestKind::Cabal | ManifestKind::CabalProject => "cabal",
+            ManifestKind::Pubspec => "pub",
+            ManifestKind::Dotnet | ManifestKind::Nuspec => "nuget",
+            ManifestKind::Plist => "xcode",
+            ManifestKind::Swift => "swift",
+            ManifestKind::BazelModule | ManifestKind::Bazel => "bazel",
+            ManifestKind::RpmSpec => "rpm",
+            ManifestKind::DebianChangelog => "deb",
+            ManifestKind::Rockspec => "luarocks",
+            ManifestKind::Sfdx => "sfdx",
+            ManifestKind::CitationCff => "cff",
+            ManifestKind::Dockerfile => "oci",
+            // Configured by the user, so there is no telling which ecosystem they belong to
+            ManifestKind::Yaml | ManifestKind::Custom | ManifestKind::VersionFile => return None,
+        };
+        Some(label)
+    }
+
+    /// The page of a package on the registry of its ecosystem, if there is a well-known one,
+    /// or on `registry` if it overrides that
+    fn registry_url(self, name: &str, registry: Option<&str>) -> Option<String> {
+        let name = encode_url_name(name);
+        let (default_registry, path) = match self {
+            ManifestKind::Cargo => ("https://crates.io", format!("crates/{}", name)),
+            ManifestKind::Npm => ("https://www.npmjs.com", format!("package/{}", name)),
+            ManifestKind::PyProject => ("https://pypi.org", format!("project/{}/", name)),
+            // Composer packages are named `vendor/package`, just like their Packagist path
+            ManifestKind::Composer => ("https://packagist.org", format!("packages/{}", name)),
+            _ => return None,
+        };
+        let registry = registry.unwrap_or(default_registry).trim_end_matches('/');
+        Some(format!("{}/{}", registry, path))
+    }
+
+    /// The registry API endpoint describing the latest release of a package,
+    /// on `registry` if it overrides the default one
+    fn latest_version_url(self, name: &str, registry: Option<&str>) -> Option<String> {
+        let name = encode_url_name(name);
+        let (default_registry, path) = match self {
+            ManifestKind::Cargo => ("https://crates.io", format!("api/v1/crates/{}", name)),
+            ManifestKind::Npm => ("https://registry.npmjs.org", format!("{}/latest", name)),
+            _ => return None,
+        };
+        let registry = registry.unwrap_or(default_registry).trim_end_matches('/');
+        Some(format!("{}/{}", registry, path))
+    }
+
+    /// The latest release version in a response from `latest_version_url`
+    fn parse_latest_version(self, response: &str) -> Option<String> {
+        let response: json::Value = json::from_str(response).ok()?;
+        let raw_version = match self {
+            ManifestKind::Cargo => response.get("crate")?.get("max_stable_version")?.as_str()?,
+            ManifestKind::Npm => response.get("version")?.as_str()?,
+            _ => return None,
+        };
+        Some(format_version(raw_version))
+    }
+
+    fn extract_version(
+        self,
+        file_contents: &str,
+        base_dir: &Path,
+        config: &PackageConfig,
+    ) -> Result<String, PackageError> {
+        match self {
+            // The `pyproject.toml` of the Python package maturin builds from the crate is probed
+            // next
+            ManifestKind::Cargo if has_static_maturin_version(base_dir)? => {
+                Err(PackageError::NotFound)
+            }
+            // Like private npm packages, crates that can't be published are internal
+            ManifestKind::Cargo
+                if config.hide_unpublishable && is_unpublishable_crate(file_contents) =>
+            {
+                Err(PackageError::NoVersionField)
+            }
+            ManifestKind::Cargo => extract_cargo_version(file_contents),
+            // Lerna monorepos keep the version of all their packages in `lerna.json`
+            ManifestKind::Npm => {
+                extract_package_version(file_contents).or_else(|error| match error {
+                    PackageError::NoVersionField => get_lerna_version(base_dir),
+                    error => Err(error),
+                })
+            }
+            // Libraries that aren't published to JSR often export their version from `mod.ts`
+            ManifestKind::Deno => {
+                extract_deno_version(file_contents).or_else(|error| match error {
+                    PackageError::NoVersionField => get_deno_module_version(base_dir),
+                    error => Err(error),
+                })
+            }
+            ManifestKind::PyProject => get_python_version(file_contents, base_dir, config),
+            ManifestKind::Composer => extract_composer_version(file_contents),
+            // Android and Kotlin Multiplatform builds often set it in `gradle.properties`
+            ManifestKind::Gradle => {
+                extract_gradle_version(file_contents).or_else(|error| match error {
+                    PackageError::NoVersionField => get_gradle_properties_version(base_dir),
+                    error => Err(error),
+                })
+            }
+            ManifestKind::Maven => extract_maven_version(file_contents),
+            ManifestKind::Ant => extract_ant_version(file_contents),
+            ManifestKind::Julia => extract_project_version(file_contents),
+            ManifestKind::Gleam => extract_project_version(file_contents),
+            ManifestKind::Mix => extract_mix_version(file_contents),
+            ManifestKind::Vcpkg => extract_vcpkg_version(file_contents),
+            ManifestKind::Racket => extract_racket_version(file_contents),
+            ManifestKind::WebExtension => extract_webext_version(file_contents),
+            ManifestKind::GrafanaPlugin => extract_plugin_json_version(file_contents),
+            ManifestKind::Zig => extract_zig_build_version(file_contents),
+            ManifestKind::CocoaPods => extract_podspec_version(file_contents),
+            ManifestKind::Gemspec => extract_gemspec_version(
+                file_contents,
+                base_dir,
+                config.resolve_gem_version_constant,
+            )
+            .or_else(|error| match error {
+                PackageError::NoVersionField if config.resolve_gemfile_lock => {
+                    get_gemfile_lock_version(file_contents, base_dir).ok_or(error)
+                }
+                _ => Err(error),
+            }),
+            ManifestKind::Chef => extract_chef_version(file_contents),
+            ManifestKind::Homebrew => extract_brew_formula_version(file_contents),
+            ManifestKind::Dune => extract_dune_version(file_contents).or_else(|error| {
+                // dune-project doesn't have to declare the version of its packages
+                match ManifestKind::Opam.read(base_dir)? {
+                    Some(opam) if error == PackageError::NoVersionField => {
+                        get_opam_version(&opam, base_dir, config)
+                    }
+                    _ => Err(error),
+                }
+            }),
+            ManifestKind::Opam => get_opam_version(file_contents, base_dir, config),
+            ManifestKind::Elm => extract_elm_version(file_contents, config.show_elm_version),
+            ManifestKind::Hpack => extract_hpack_version(file_contents),
+            ManifestKind::Cabal => extract_cabal_version(file_contents),
+            ManifestKind::CabalProject => get_cabal_project_version(file_contents, base_dir),
+            // Apps that aren't published to pub.dev are internal
+            ManifestKind::Pubspec
+                if config.hide_unpublishable && is_unpublishable_pubspec(file_contents) =>
+            {
+                Err(PackageError::NoVersionField)
+            }
+            ManifestKind::Pubspec => extract_yaml_version(file_contents),
+            ManifestKind::Dotnet => extract_dotnet_version(file_contents),
+            ManifestKind::Nuspec => extract_nuspec_version(file_contents),
+            ManifestKind::Plist => extract_plist_version(file_contents),
+            ManifestKind::Swift => get_swift_version(base_dir, config),
+            ManifestKind::BazelModule => extract_bazel_module_version(file_contents),
+            ManifestKind::Bazel => extract_bazel_build_version(file_contents),
+            ManifestKind::RpmSpec => extract_rpm_spec_version(file_contents),
+            ManifestKind::DebianChangelog => {
+                extract_debian_changelog_version(file_contents, config.strip_debian_revision)
+            }
+            ManifestKind::Rockspec => extract_rockspec_version(file_contents),
+            ManifestKind::Sfdx => extract_sfdx_version(file_contents),
+            ManifestKind::CitationCff => extract_citation_cff_version(file_contents),
+            ManifestKind::Dockerfile => extract_dockerfile_version(file_contents),
+            ManifestKind::Yaml => extract_yaml_version(file_contents),
+            // The key to read is configured along with the file, see `extract_custom_version`
+            ManifestKind::Custom => Err(PackageError::NotFound),
+            ManifestKind::VersionFile => extract_plain_version(file_contents),
+        }
+    }
+}
+
+/// What a package produces, used to pick a symbol from `symbol_variants`
+#[derive(Debug, PartialEq)]
+enum PackageKind {
+    Lib,
+    Bin,
+}
+
+impl PackageKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PackageKind::Lib => "lib",
+            PackageKind::Bin => "bin",
+        }
+    }
+}
+
+/// The reasons a package version could not be determined
+#[derive(Debug, PartialEq)]
+enum PackageError {
+    /// None of the supported manifests exist in the directory
+    NotFound,
+    /// A manifest exists but its contents could not be parsed
+    ParseFailed,
+    /// A manifest was parsed but doesn't declare a displayable version
+    NoVersionField,
+    /// A manifest exists but could not be read
+    Io(io::ErrorKind),
+}
+
+impl From<io::Error> for PackageError {
+    fn from(error: io::Error) -> Self {
+        PackageError::Io(error.kind())
+    }
+}
+
+/// The patterns of the regex-based extractors, keyed by name
+const VERSION_PATTERNS: &[(&str, &str)] = &[
+    (
+        "flit_version",
+        r#"(?m)^__version__\s*=\s*['"](?P<version>[^'"]+)['"]"#,
+    ),
+    (
+        "gradle",
+        r#"(?m)^version(?: | ?= ?)['"](?P<version>[^'"]+)['"]$"#,
+    ),
+    ("mix", r#"(?m)version: "(?P<version>[^"]+)""#),
+    (
+        "racket",
+        r#"\(define\s+version\s+"(?P<version>[^"]+)"\s*\)"#,
+    ),
+    (
+        "zig",
+        r#"(?m)^\s*(?:pub\s+)?const\s+version\s*=\s*"(?P<version>[^"]+)"\s*;"#,
+    ),
+    (
+        "ruby_spec_version",
+        r#"(?m)^\s*\w+\.version\s*=\s*(?:Gem::Version\.new[\s(]*)?["'](?P<version>[^"']+)["']"#,
+    ),
+    (
+        "ruby_spec_version_constant",
+        r#"(?m)^\s*\w+\.version\s*=\s*[A-Z][\w:]*::VERSION\b"#,
+    ),
+    (
+        "ruby_spec_name",
+        r#"(?m)^\s*\w+\.name\s*=\s*["'](?P<name>[^"']+)["']"#,
+    ),
+    (
+        "ruby_version_constant",
+        r#"(?m)^\s*VERSION\s*=\s*["'](?P<version>[^"']+)["']"#,
+    ),
+    (
+        "dune",
+        r#"(?m)^\s*\(version\s+"?(?P<version>[^\s"()]+)"?\s*\)"#,
+    ),
+    ("opam", r#"(?m)^version:\s*"(?P<version>[^"]+)""#),
+    ("csproj", r#"<Version>\s*(?P<version>[^<\s]+)\s*</Version>"#),
+    (
+        "csproj_prefix",
+        r#"<VersionPrefix>\s*(?P<version>[^<\s]+)\s*</VersionPrefix>"#,
+    ),
+    (
+        "csproj_suffix",
+        r#"<VersionSuffix>\s*(?P<suffix>[^<\s]+)\s*</VersionSuffix>"#,
+    ),
+    (
+        "readme_badge",
+        r#"shields\.io/badge/version-(?P<version>v?\d(?:[0-9A-Za-z.+]|--)*)-"#,
+    ),
+    ("ant_property", r#"<property\b(?P<attributes>[^>]*)>"#),
+    (
+        "xml_attribute",
+        r#"(?P<name>[\w.:-]+)\s*=\s*(?:"(?P<double>[^"]*)"|'(?P<single>[^']*)')"#,
+    ),
+    (
+        "brew_formula_class",
+        r#"(?m)^\s*class\s+\w+\s*<\s*Formula\b"#,
+    ),
+    (
+        "ruby_version_call",
+        r#"(?m)^\s*version\s+["'](?P<version>[^"']+)["']"#,
+    ),
+    (
+        "brew_formula_url",
+        r#"(?m)^\s*url\s+["'][^"']*?[-_/]v?(?P<version>\d+(?:\.\d+)+)\.(?:tar|tgz|zip)"#,
+    ),
+    ("mix_version_attribute", r#"version:\s*@(?P<name>\w+)"#),
+    (
+        "mix_attribute",
+        r#"(?m)^\s*@(?P<name>\w+)\s+"(?P<value>[^"]+)""#,
+    ),
+    (
+        "nuspec",
+        r#"(?s)<metadata\b[^>]*>.*?<version>\s*(?P<version>[^<\s]+)\s*</version>"#,
+    ),
+    ("cabal", r#"(?mi)^version:[ \t]*(?P<version>\S+)"#),
+    ("calver", r#"^v?(?:19|20)\d{2}[.-]\d"#),
+    (
+        "plist",
+        r#"<key>\s*CFBundleShortVersionString\s*</key>\s*<string>\s*(?P<version>[^<\s]+)\s*</string>"#,
+    ),
+    (
+        "bazel_module",
+        r#"(?s)(?:^|\n)module\s*\([^)]*?\bversion\s*=\s*"(?P<version>[^"]+)""#,
+    ),
+    (
+        "bazel_rule_version",
+        r#"(?s)(?:^|\n)(?:pkg_\w+|py_wheel|npm_package)\s*\((?:[^()]|\([^()]*\))*?\bversion\s*=\s*"(?P<version>[^"]+)""#,
+    ),
+    (
+        "escape_sequence",
+        r#"\x1b(?:\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(?:\x07|\x1b\\)?|.)"#,
+    ),
+    ("rpm_spec", r#"(?mi)^Version:[ \t]*(?P<version>\S+)"#),
+    (
+        "git_describe",
+        r#"^(?P<tag>.+)-(?P<distance>\d+)-g[0-9a-f]+(?P<dirty>\+dirty)?$"#,
+    ),
+    (
+        "debian_changelog",
+        r#"\A\s*[a-z0-9][a-z0-9+.-]+ \((?P<version>[^)\s]+)\)"#,
+    ),
+    (
+        "rockspec",
+        r#"(?m)^\s*version\s*=\s*["'](?P<version>[^"']+)["']"#,
+    ),
+    (
+        "gradle_properties",
+        r#"(?m)^[ \t]*(?:version|VERSION_NAME)[ \t]*[=:][ \t]*(?P<version>\S+)[ \t]*$"#,
+    ),
+    (
+        "msbuild_property_ref",
+        r#"\$\((?P<name>[A-Za-z_][\w.-]*)\)"#,
+    ),
+    (
+        "cabal_project_packages",
+        r#"(?m)^packages:(?P<packages>.*(?:\n[ \t]+.*)*)"#,
+    ),
+    (
+        "dockerfile_label",
+        r#"(?mi)^[ \t]*LABEL[ \t]+(?P<labels>.*)$"#,
+    ),
+    (
+        "dockerfile_label_pair",
+        r#"(?P<key>[\w.-]+)=(?:"(?P<double>[^"]*)"|'(?P<single>[^']*)'|(?P<bare>\S+))"#,
+    ),
+    (
+        "maven_version",
+        r#"(?s)<version>\s*(?P<version>[^<]*?)\s*</version>"#,
+    ),
+    (
+        "maven_nested_sections",
+        r#"(?s)<parent>.*?</parent>|<dependencyManagement>.*?</dependencyManagement>|<dependencies>.*?</dependencies>|<build>.*?</build>|<profiles>.*?</profiles>|<reporting>.*?</reporting>|<properties>.*?</properties>"#,
+    ),
+    ("maven_parent", r#"(?s)<parent>(?P<parent>.*?)</parent>"#),
+    (
+        "maven_properties",
+        r#"(?s)<properties>(?P<properties>.*?)</properties>"#,
+    ),
+    ("maven_property_ref", r#"\$\{(?P<name>[\w.-]+)\}"#),
+    (
+        "deno_module_version",
+        r#"(?m)^\s*export\s+const\s+VERSION\s*=\s*["'](?P<version>[^"']+)["']"#,
+    ),
+];
+
+static VERSION_REGEXES: Lazy<HashMap<&str, Regex>> = Lazy::new(|| {
+    VERSION_PATTERNS
+        .iter()
+        .map(|&(name, pattern)| (name, Regex::new(pattern).unwrap()))
+        .collect()
+});
+
+/// The compiled pattern registered as `name` in `VERSION_PATTERNS`
+fn version_regex(name: &str) -> &'static Regex {
+    VERSION_REGEXES
+        .get(name)
+        .unwrap_or_else(|| panic!("No version pattern named {}", name))
+}
+
+fn extract_cargo_version(file_contents: &str) -> Result<String, PackageError> {
+    let cargo_toml: toml::Value =
+        toml::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+    let package = match cargo_toml.get("package") {
+        Some(package) => package,
+        None => return extract_cargo_workspace_version(&cargo_toml),
+    };
+    let raw_version = package
+        .get("version")
+        .and_then(toml::Value::as_str)
+        .ok_or(PackageError::NoVersionField)?;
 
     let formatted_version = format_version(raw_version);
-    Some(formatted_version)
+    Ok(formatted_version)
 }
 
-fn extract_package_version(file_contents: &str) -> Option<String> {
-    let package_json: json::Value = json::from_str(file_contents).ok()?;
+/// The version in `[workspace.package]` of a virtual workspace manifest, i.e. one
+/// at the workspace root without a `[package]` of its own
+fn extract_cargo_workspace_version(cargo_toml: &toml::Value) -> Result<String, PackageError> {
+    let raw_version = cargo_toml
+        .get("workspace")
+        .and_then(|workspace| workspace.get("package")?.get("version")?.as_str())
+        .ok_or(PackageError::NoVersionField)?;
+
+    Ok(format_version(raw_version))
+}
+
+/// Whether `base_dir` has a `pyproject.toml` built by maturin that sets its own version,
+/// rather than listing `version` in `dynamic` to take the one in `Cargo.toml`
+fn has_static_maturin_version(base_dir: &Path) -> Result<bool, PackageError> {
+    let pyproject_toml: toml::Value = match read_manifest(base_dir, "pyproject.toml")? {
+        Some(file_contents) => match toml::from_str(&file_contents) {
+            Ok(pyproject_toml) => pyproject_toml,
+            Err(_) => return Ok(false),
+        },
+        None => return Ok(false),
+    };
+    let build_backend = pyproject_toml
+        .get("build-system")
+        .and_then(|build_system| build_system.get("build-backend")?.as_str());
+    let version = pyproject_toml
+        .get("project")
+        .and_then(|project| project.get("version")?.as_str());
+    Ok(build_backend == Some("maturin") && version.is_some())
+}
+
+/// Whether a crate sets `publish = false`, or `publish = []` allowing no registry
+fn is_unpublishable_crate(file_contents: &str) -> bool {
+    let cargo_toml: toml::Value = match toml::from_str(file_contents) {
+        Ok(cargo_toml) => cargo_toml,
+        Err(_) => return false,
+    };
+
+    match cargo_toml
+        .get("package")
+        .and_then(|package| package.get("publish"))
+    {
+        Some(toml::Value::Boolean(publish)) => !publish,
+        Some(toml::Value::Array(registries)) => registries.is_empty(),
+        _ => false,
+    }
+}
+
+fn extract_package_version(file_contents: &str) -> Result<String, PackageError> {
+    let package_json: json::Value =
+        json::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
 
     if package_json.get("private").and_then(json::Value::as_bool) == Some(true) {
-        return None;
+        return Err(PackageError::NoVersionField);
     }
 
-    let raw_version = package_json.get("version")?.as_str()?;
+    let raw_version = package_json
+        .get("version")
+        .and_then(json::Value::as_str)
+        .ok_or(PackageError::NoVersionField)?;
     if raw_version == "null" {
-        return None;
+        return Err(PackageError::NoVersionField);
     };
 
     let formatted_version = format_version(raw_version);
-    Some(formatted_version)
+    Ok(formatted_version)
 }
 
-fn extract_poetry_version(file_contents: &str) -> Option<String> {
-    let poetry_toml: toml::Value = toml::from_str(file_contents).ok()?;
-    let raw_version = poetry_toml
-        .get("tool")?
-        .get("poetry")?
-        .get("version")?
-        .as_str()?;
+/// The version of a Lerna monorepo in fixed mode, from the `lerna.json` next to `package.json`.
+/// In independent mode each package has its own version, so there is none to show.
+fn get_lerna_version(base_dir: &Path) -> Result<String, PackageError> {
+    let lerna_json = read_manifest(base_dir, "lerna.json")?.ok_or(PackageError::NoVersionField)?;
+    extract_lerna_version(&lerna_json)
+}
+
+fn extract_lerna_version(file_contents: &str) -> Result<String, PackageError> {
+    let lerna_json: json::Value =
+        json::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+    let raw_version = lerna_json
+        .get("version")
+        .and_then(json::Value::as_str)
+        .filter(|version| *version != "independent")
+        .ok_or(PackageError::NoVersionField)?;
 
     let formatted_version = format_version(raw_version);
-    Some(formatted_version)
+    Ok(formatted_version)
 }
 
-fn extract_gradle_version(file_contents: &str) -> Option<String> {
-    let re = Regex::new(r#"(?m)^version ['"](?P<version>[^'"]+)['"]$"#).unwrap();
-    let caps = re.captures(file_contents)?;
+fn extract_deno_version(file_contents: &str) -> Result<String, PackageError> {
+    let deno_json: json::Value =
+        json::from_str(&strip_jsonc(file_contents)).map_err(|_| PackageError::ParseFailed)?;
+    let raw_version = deno_json
+        .get("version")
+        .and_then(json::Value::as_str)
+        .ok_or(PackageError::NoVersionField)?;
 
-    let formatted_version = format_version(&caps["version"]);
-    Some(formatted_version)
+    let formatted_version = format_version(raw_version);
+    Ok(formatted_version)
 }
 
-fn extract_composer_version(file_contents: &str) -> Option<String> {
-    let composer_json: json::Value = json::from_str(file_contents).ok()?;
-    let raw_version = composer_json.get("version")?.as_str()?;
-    if raw_version == "null" {
-        return None;
-    };
+fn get_deno_module_version(base_dir: &Path) -> Result<String, PackageError> {
+    for module_name in &["mod.ts", "mod.js"] {
+        if let Some(module) = read_manifest(base_dir, module_name)? {
+            return extract_deno_module_version(&module);
+        }
+    }
+    Err(PackageError::NoVersionField)
+}
+
+/// The `VERSION` exported by the entry point of a Deno library, e.g. `export const VERSION = "1.2.3"`
+fn extract_deno_module_version(file_contents: &str) -> Result<String, PackageError> {
+    let caps = version_regex("deno_module_version")
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+fn extract_poetry_version(file_contents: &str) -> Result<String, PackageError> {
+    let poetry_toml: toml::Value =
+        toml::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+    let raw_version = poetry_toml
+        .get("tool")
+        .and_then(|tool| tool.get("poetry")?.get("version")?.as_str())
+        .ok_or(PackageError::NoVersionField)?;
 
     let formatted_version = format_version(raw_version);
-    Some(formatted_version)
+    Ok(formatted_version)
 }
 
-fn extract_project_version(file_contents: &str) -> Option<String> {
-    let project_toml: toml::Value = toml::from_str(file_contents).ok()?;
-    let raw_version = project_toml.get("version")?.as_str()?;
+/// The version commitizen bumps in `[tool.commitizen]`, for projects whose `[project]` version
+/// is dynamic
+fn extract_commitizen_version(file_contents: &str) -> Result<String, PackageError> {
+    let pyproject_toml: toml::Value =
+        toml::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+    let raw_version = pyproject_toml
+        .get("tool")
+        .and_then(|tool| tool.get("commitizen")?.get("version")?.as_str())
+        .ok_or(PackageError::NoVersionField)?;
 
     let formatted_version = format_version(raw_version);
-    Some(formatted_version)
+    Ok(formatted_version)
 }
 
-fn extract_mix_version(file_contents: &str) -> Option<String> {
-    let re = Regex::new(r#"(?m)version: "(?P<version>[^"]+)""#).unwrap();
-    let caps = re.captures(file_contents)?;
+fn extract_flit_version(file_contents: &str, base_dir: &Path) -> Result<String, PackageError> {
+    let flit_toml: toml::Value =
+        toml::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+    let flit_metadata = flit_toml
+        .get("tool")
+        .and_then(|tool| tool.get("flit")?.get("metadata"))
+        .ok_or(PackageError::NoVersionField)?;
+
+    if let Some(raw_version) = flit_metadata.get("version").and_then(toml::Value::as_str) {
+        return Ok(format_version(raw_version));
+    }
+
+    // Flit reads the version from the `__version__` of the module being packaged
+    let module_name = flit_metadata
+        .get("module")
+        .and_then(toml::Value::as_str)
+        .ok_or(PackageError::NoVersionField)?;
+    let module_dir = base_dir.join(module_name);
+    let module_file = utils::read_file(module_dir.join("__init__.py"))
+        .or_else(|_| utils::read_file(module_dir.with_extension("py")))
+        .map_err(|_| PackageError::NoVersionField)?;
+
+    let re = version_regex("flit_version");
+    let caps = re
+        .captures(&module_file)
+        .ok_or(PackageError::NoVersionField)?;
 
-    let formatted_version = format_version(&caps["version"]);
-    Some(formatted_version)
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
 }
 
-fn get_package_version(base_dir: &PathBuf) -> Option<String> {
-    if let Ok(cargo_toml) = utils::read_file(base_dir.join("Cargo.toml")) {
-        extract_cargo_version(&cargo_toml)
-    } else if let Ok(package_json) = utils::read_file(base_dir.join("package.json")) {
-        extract_package_version(&package_json)
-    } else if let Ok(poetry_toml) = utils::read_file(base_dir.join("pyproject.toml")) {
-        extract_poetry_version(&poetry_toml)
-    } else if let Ok(composer_json) = utils::read_file(base_dir.join("composer.json")) {
-        extract_composer_version(&composer_json)
-    } else if let Ok(build_gradle) = utils::read_file(base_dir.join("build.gradle")) {
-        extract_gradle_version(&build_gradle)
-    } else if let Ok(project_toml) = utils::read_file(base_dir.join("Project.toml")) {
-        extract_project_version(&project_toml)
-    } else if let Ok(mix_file) = utils::read_file(base_dir.join("mix.exs")) {
-        extract_mix_version(&mix_file)
-    } else {
-        None
+fn extract_pep621_version(
+    file_contents: &str,
+    base_dir: &Path,
+    config: &PackageConfig,
+) -> Result<String, PackageError> {
+    let pyproject_toml: toml::Value =
+        toml::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+    let project = pyproject_toml
+        .get("project")
+        .ok_or(PackageError::NoVersionField)?;
+
+    if let Some(raw_version) = project.get("version").and_then(toml::Value::as_str) {
+        return Ok(format_version(raw_version));
+    }
+
+    // setuptools-scm computes dynamic versions from the tags of the git repository
+    let is_dynamic_version = project
+        .get("dynamic")
+        .and_then(toml::Value::as_array)
+        .is_some_and(|fields| fields.iter().any(|field| field.as_str() == Some("version")));
+    let uses_setuptools_scm = pyproject_toml
+        .get("tool")
+        .and_then(|tool| tool.get("setuptools_scm"))
+        .is_some();
+    if !is_dynamic_version || !uses_setuptools_scm || !config.resolve_git_version {
+        return Err(PackageError::NoVersionField);
     }
+
+    cached_version(&base_dir.join("pyproject.toml"), config, || {
+        get_git_describe_version(base_dir, config.require_clean_repo, config.describe_format)
+    })
+    .ok_or(PackageError::NoVersionField)
 }
 
-fn format_version(version: &str) -> String {
-    let cleaned = version.replace('"', "").trim().to_string();
-    if cleaned.starts_with('v') {
-        cleaned
-    } else {
-        format!("v{}", cleaned)
+/// Try each of the ways a `pyproject.toml` can declare a version, in order
+fn get_python_version(
+    file_contents: &str,
+    base_dir: &Path,
+    config: &PackageConfig,
+) -> Result<String, PackageError> {
+    extract_poetry_version(file_contents)
+        .or_else(|error| match error {
+            PackageError::NoVersionField => extract_pep621_version(file_contents, base_dir, config),
+            error => Err(error),
+        })
+        .or_else(|error| match error {
+            PackageError::NoVersionField => extract_commitizen_version(file_contents),
+            error => Err(error),
+        })
+        .or_else(|error| match error {
+            PackageError::NoVersionField => extract_flit_version(file_contents, base_dir),
+            error => Err(error),
+        })
+        .or_else(|error| match error {
+            PackageError::NoVersionField => get_uv_workspace_version(base_dir),
+            error => Err(error),
+        })
+}
+
+/// Read the version of the uv workspace `base_dir` is a member of from the workspace root
+fn get_uv_workspace_version(base_dir: &Path) -> Result<String, PackageError> {
+    for root_dir in base_dir.ancestors().skip(1) {
+        let root_pyproject = match read_manifest(root_dir, "pyproject.toml")? {
+            Some(root_pyproject) => root_pyproject,
+            None => continue,
+        };
+        let root_toml: toml::Value =
+            toml::from_str(&root_pyproject).map_err(|_| PackageError::ParseFailed)?;
+        let workspace = match root_toml
+            .get("tool")
+            .and_then(|tool| tool.get("uv")?.get("workspace"))
+        {
+            Some(workspace) => workspace,
+            None => continue,
+        };
+
+        let member_dir = base_dir
+            .strip_prefix(root_dir)
+            .map_err(|_| PackageError::NotFound)?;
+        let matches_any = |key: &str| {
+            workspace
+                .get(key)
+                .and_then(toml::Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(toml::Value::as_str)
+                .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+                .any(|pattern| pattern.matches_path(member_dir))
+        };
+        if !matches_any("members") || matches_any("exclude") {
+            return Err(PackageError::NoVersionField);
+        }
+
+        let raw_version = root_toml
+            .get("project")
+            .and_then(|project| project.get("version")?.as_str())
+            .ok_or(PackageError::NoVersionField)?;
+        return Ok(format_version(raw_version));
     }
+
+    Err(PackageError::NoVersionField)
+}
+
+/// Where the package module keeps what is too expensive to find out on every prompt
+#[cfg(not(test))]
+fn package_cache_dir() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join("starship").join("package"))
 }
 
+/// Tests keep their cache out of the user's cache directory
 #[cfg(test)]
-mod tests {
-    use super::*;
+fn package_cache_dir() -> Option<PathBuf> {
+    Some(std::env::temp_dir().join("starship-tests").join("package"))
+}
 
-    #[test]
-    fn test_format_version() {
-        assert_eq!(format_version("0.1.0"), "v0.1.0");
-        assert_eq!(format_version(" 0.1.0 "), "v0.1.0");
-        assert_eq!(format_version("0.1.0 "), "v0.1.0");
-        assert_eq!(format_version(" 0.1.0"), "v0.1.0");
-        assert_eq!(format_version("\"0.1.0\""), "v0.1.0");
+/// The name of the cache file of `key`, e.g. a URL or the path of a manifest
+fn cache_file_name(key: &str) -> String {
+    key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
 
-        assert_eq!(format_version("v0.1.0"), "v0.1.0");
-        assert_eq!(format_version(" v0.1.0 "), "v0.1.0");
-        assert_eq!(format_version(" v0.1.0"), "v0.1.0");
-        assert_eq!(format_version("v0.1.0 "), "v0.1.0");
-        assert_eq!(format_version("\"v0.1.0\""), "v0.1.0");
+/// Resolve the version `git describe` gives for `manifest` with `resolve`, unless it was already
+/// resolved with the same `config` since the manifest was last modified and the commit or tags
+/// of its repository last changed. Starship runs once per prompt, so the versions are cached on
+/// disk.
+fn cached_version<F>(manifest: &Path, config: &PackageConfig, resolve: F) -> Option<String>
+where
+    F: FnOnce() -> Option<String>,
+{
+    match package_cache_dir() {
+        Some(cache_dir) => {
+            cached_version_in(&cache_dir.join("versions"), manifest, config, resolve)
+        }
+        None => resolve(),
     }
+}
 
-    #[test]
-    fn test_extract_cargo_version() {
-        let cargo_with_version = toml::toml! {
-            [package]
-            name = "starship"
-            version = "0.1.0"
+/// Like `cached_version`, with the cache in `cache_dir`. Each cache file holds the manifest
+/// path, its modification time, the state of its repository and the options the version was
+/// resolved with on the first four lines, and the version (empty if there is none) on the last
+/// one.
+fn cached_version_in<F>(
+    cache_dir: &Path,
+    manifest: &Path,
+    config: &PackageConfig,
+    resolve: F,
+) -> Option<String>
+where
+    F: FnOnce() -> Option<String>,
+{
+    let modified = match fs::metadata(manifest).and_then(|metadata| metadata.modified()) {
+        Ok(modified) => modified,
+        Err(_) => return resolve(),
+    };
+    let modified = modified
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let repository_state = manifest
+        .parent()
+        .map(|dir| get_repository_state(dir, config.require_clean_repo))
+        .unwrap_or_default();
+    let key = format!(
+        "{}\n{}\n{}\n{:?} {}\n",
+        manifest.display(),
+        modified,
+        repository_state,
+        config.describe_format,
+        config.require_clean_repo
+    );
+
+    let cache_file = cache_dir.join(cache_file_name(&manifest.to_string_lossy()));
+    if let Ok(contents) = utils::read_file(&cache_file) {
+        if let Some(version) = contents.strip_prefix(&key) {
+            log::trace!("Using cached version of {:?}", manifest);
+            return Some(version.to_string()).filter(|version| !version.is_empty());
         }
-        .to_string();
+    }
 
-        let expected_version = Some("v0.1.0".to_string());
-        assert_eq!(extract_cargo_version(&cargo_with_version), expected_version);
+    let version = resolve();
+    let cache_contents = format!("{}{}", key, version.as_deref().unwrap_or_default());
+    if let Err(error) =
+        fs::create_dir_all(cache_dir).and_then(|_| fs::write(&cache_file, cache_contents))
+    {
+        log::debug!("Unable to cache the version of {:?}: {}", manifest, error);
+    }
+    version
+}
 
-        let cargo_without_version = toml::toml! {
+/// What `git describe` depends on in the repository containing `dir`: the commit checked out,
+/// when its tags last changed and, with `check_dirty`, whether it has uncommitted changes.
+/// An empty string outside of repositories.
+fn get_repository_state(dir: &Path, check_dirty: bool) -> String {
+    let repository = match git2::Repository::discover(dir) {
+        Ok(repository) => repository,
+        Err(_) => return String::new(),
+    };
+    let head = repository
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .map(|commit| commit.to_string())
+        .unwrap_or_default();
+    // Tags are stored either as files in `refs/tags` or in `packed-refs`
+    let modified = |path: PathBuf| {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or(0, |modified| modified.as_nanos())
+    };
+    let git_dir = repository.path();
+    let packed_refs = modified(git_dir.join("packed-refs"));
+    let tags = modified(git_dir.join("refs").join("tags"));
+    let dirty = check_dirty
+        && repository
+            .statuses(Some(git2::StatusOptions::new().include_untracked(false)))
+            .is_ok_and(|statuses| !statuses.is_empty());
+    format!("{} {} {} {}", head, packed_refs, tags, dirty)
+}
+
+/// The version `git describe --tags` gives for the repository containing `base_dir`,
+/// e.g. `v1.2.3` on a tagged commit or `v1.2.3-2-gabc1234` two commits after it.
+/// With `mark_dirty`, uncommitted changes are marked by a `+dirty` suffix.
+fn get_git_describe_version(
+    base_dir: &Path,
+    mark_dirty: bool,
+    describe_format: DescribeFormat,
+) -> Option<String> {
+    let repository = git2::Repository::discover(base_dir).ok()?;
+    let describe = repository
+        .describe(git2::DescribeOptions::new().describe_tags())
+        .ok()?;
+    let mut format_options = git2::DescribeFormatOptions::new();
+    if mark_dirty {
+        format_options.dirty_suffix("+dirty");
+    }
+    let raw_version = describe.format(Some(&format_options)).ok()?;
+
+    Some(format_version(&format_describe(
+        &raw_version,
+        describe_format,
+    )))
+}
+
+/// Shorten `git describe` output like `v1.2.3-5-gabc1234` to the parts `describe_format` keeps,
+/// keeping the `+dirty` suffix of repositories with uncommitted changes
+fn format_describe(describe: &str, describe_format: DescribeFormat) -> String {
+    let caps = match version_regex("git_describe").captures(describe) {
+        Some(caps) if describe_format != DescribeFormat::Full => caps,
+        // Commits that are tagged are described by just their tag
+        _ => return describe.to_string(),
+    };
+    let dirty = caps.name("dirty").map_or("", |dirty| dirty.as_str());
+
+    match describe_format {
+        DescribeFormat::TagDistance => format!("{}+{}{}", &caps["tag"], &caps["distance"], dirty),
+        _ => format!("{}{}", &caps["tag"], dirty),
+    }
+}
+
+fn extract_gradle_version(file_contents: &str) -> Result<String, PackageError> {
+    let re = version_regex("gradle");
+    let caps = re
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+/// The `<version>` of a Maven project, inherited from its `<parent>` if it has none.
+/// CI-friendly versions like `${revision}${changelist}` are resolved against `<properties>`.
+fn extract_maven_version(file_contents: &str) -> Result<String, PackageError> {
+    // The versions of the parent, dependencies and plugins aren't the version of the project
+    let project = version_regex("maven_nested_sections").replace_all(file_contents, "");
+    let caps = version_regex("maven_version")
+        .captures(&project)
+        .or_else(|| {
+            let parent = version_regex("maven_parent").captures(file_contents)?;
+            let parent = parent.name("parent")?.as_str();
+            version_regex("maven_version").captures(parent)
+        })
+        .ok_or(PackageError::NoVersionField)?;
+
+    let properties = version_regex("maven_properties")
+        .captures(file_contents)
+        .and_then(|caps| caps.name("properties"))
+        .map_or("", |properties| properties.as_str());
+    let raw_version =
+        resolve_property_references(&caps["version"], "maven_property_ref", |name| {
+            find_xml_property(properties, name)
+        })?;
+
+    let formatted_version = format_captured_version(&raw_version)?;
+    Ok(formatted_version)
+}
+
+/// Replace the references to properties in `value`, matched by the `name` of the `reference`
+/// pattern in `VERSION_PATTERNS`, with their definition given by `lookup`, e.g. `$(Version)` in
+/// an MSBuild project. Properties defined elsewhere, like on the command line or in an imported
+/// file, can't be resolved.
+fn resolve_property_references<F>(
+    value: &str,
+    reference: &str,
+    lookup: F,
+) -> Result<String, PackageError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    // Properties can reference each other, but not endlessly
+    const MAX_DEPTH: usize = 8;
+
+    let reference = version_regex(reference);
+    let mut value = value.to_string();
+    for _ in 0..MAX_DEPTH {
+        if !reference.is_match(&value) {
+            return Ok(value);
+        }
+
+        let mut unresolved = false;
+        value = reference
+            .replace_all(&value, |caps: &regex::Captures| {
+                lookup(&caps["name"]).unwrap_or_else(|| {
+                    unresolved = true;
+                    String::new()
+                })
+            })
+            .into_owned();
+        if unresolved {
+            return Err(PackageError::NoVersionField);
+        }
+    }
+
+    Err(PackageError::NoVersionField)
+}
+
+/// The text of the first `<name>` element of `xml`, if it has no child elements, or an empty
+/// string for an empty element like `<sha1/>`
+fn find_xml_property(xml: &str, name: &str) -> Option<String> {
+    let open_tag = format!("<{}>", name);
+    if let Some(start) = xml.find(&open_tag) {
+        let rest = &xml[start + open_tag.len()..];
+        let value = &rest[..rest.find(&format!("</{}>", name))?];
+        return Some(value.trim().to_string()).filter(|value| !value.contains('<'));
+    }
+
+    let is_empty = xml.contains(&format!("<{}/>", name)) || xml.contains(&format!("<{} />", name));
+    Some(String::new()).filter(|_| is_empty)
+}
+
+fn get_gradle_properties_version(base_dir: &Path) -> Result<String, PackageError> {
+    let properties =
+        read_manifest(base_dir, "gradle.properties")?.ok_or(PackageError::NoVersionField)?;
+    extract_gradle_properties_version(&properties)
+}
+
+/// The `version` or `VERSION_NAME` property of a `gradle.properties`
+fn extract_gradle_properties_version(file_contents: &str) -> Result<String, PackageError> {
+    let caps = version_regex("gradle_properties")
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+fn extract_ant_version(file_contents: &str) -> Result<String, PackageError> {
+    let raw_version = version_regex("ant_property")
+        .captures_iter(file_contents)
+        .find_map(|property| {
+            let mut name = None;
+            let mut value = None;
+            for attribute in version_regex("xml_attribute").captures_iter(&property["attributes"]) {
+                let attribute_value = attribute
+                    .name("double")
+                    .or_else(|| attribute.name("single"))
+                    .map(|value| value.as_str());
+                match &attribute["name"] {
+                    "name" => name = attribute_value,
+                    "value" => value = attribute_value,
+                    _ => {}
+                }
+            }
+
+            if name == Some("version") {
+                value.map(str::to_string)
+            } else {
+                None
+            }
+        })
+        .ok_or(PackageError::NoVersionField)?;
+
+    format_captured_version(&raw_version)
+}
+
+fn extract_composer_version(file_contents: &str) -> Result<String, PackageError> {
+    let composer_json: json::Value =
+        json::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+    let raw_version = composer_json
+        .get("version")
+        .and_then(json::Value::as_str)
+        .ok_or(PackageError::NoVersionField)?;
+    if raw_version == "null" {
+        return Err(PackageError::NoVersionField);
+    };
+
+    let formatted_version = format_version(raw_version);
+    Ok(formatted_version)
+}
+
+fn extract_project_version(file_contents: &str) -> Result<String, PackageError> {
+    let project_toml: toml::Value =
+        toml::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+    let raw_version = project_toml
+        .get("version")
+        .and_then(toml::Value::as_str)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_version(raw_version);
+    Ok(formatted_version)
+}
+
+fn extract_mix_version(file_contents: &str) -> Result<String, PackageError> {
+    let re = version_regex("mix");
+    if let Some(caps) = re.captures(file_contents) {
+        return format_captured_version(&caps["version"]);
+    }
+
+    // Projects often keep the version in a module attribute, e.g. `version: @version`
+    let attribute = version_regex("mix_version_attribute")
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+    let raw_version = version_regex("mix_attribute")
+        .captures_iter(file_contents)
+        .find(|caps| caps["name"] == attribute["name"])
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&raw_version["value"])?;
+    Ok(formatted_version)
+}
+
+fn extract_vcpkg_version(file_contents: &str) -> Result<String, PackageError> {
+    let vcpkg_json: json::Value =
+        json::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+    let raw_version = [
+        "version",
+        "version-semver",
+        "version-date",
+        "version-string",
+    ]
+    .iter()
+    .find_map(|key| vcpkg_json.get(key)?.as_str())
+    .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_version(raw_version);
+    Ok(formatted_version)
+}
+
+fn extract_racket_version(file_contents: &str) -> Result<String, PackageError> {
+    let re = version_regex("racket");
+    let caps = re
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+/// Read the top-level `version` of a JSON manifest
+fn json_version(manifest_json: &json::Value) -> Result<String, PackageError> {
+    let raw_version = manifest_json
+        .get("version")
+        .and_then(json::Value::as_str)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_version(raw_version);
+    Ok(formatted_version)
+}
+
+/// Elm applications have no version of their own, but pin the exact compiler version
+/// in `elm-version`, which is shown instead if `show_compiler_version` is set
+fn extract_elm_version(
+    file_contents: &str,
+    show_compiler_version: bool,
+) -> Result<String, PackageError> {
+    let elm_json: json::Value =
+        json::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+    let is_application = elm_json.get("type").and_then(json::Value::as_str) == Some("application");
+    if !is_application || !show_compiler_version {
+        return json_version(&elm_json);
+    }
+
+    let raw_version = elm_json
+        .get("elm-version")
+        .and_then(json::Value::as_str)
+        .ok_or(PackageError::NoVersionField)?;
+    let formatted_version = format_version(raw_version);
+    Ok(formatted_version)
+}
+
+fn extract_webext_version(file_contents: &str) -> Result<String, PackageError> {
+    let manifest_json: json::Value =
+        json::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+
+    // `manifest.json` is a common name, only WebExtensions declare a `manifest_version`
+    if manifest_json.get("manifest_version").is_none() {
+        return Err(PackageError::NotFound);
+    }
+
+    json_version(&manifest_json)
+}
+
+fn extract_plugin_json_version(file_contents: &str) -> Result<String, PackageError> {
+    let plugin_json: json::Value =
+        json::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+
+    // `plugin.json` is a common name, Grafana plugins always declare their `type` and `id`
+    if plugin_json.get("type").is_none() || plugin_json.get("id").is_none() {
+        return Err(PackageError::NotFound);
+    }
+
+    let raw_version = plugin_json
+        .pointer("/info/version")
+        .and_then(json::Value::as_str)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_version(raw_version);
+    Ok(formatted_version)
+}
+
+fn extract_zig_build_version(file_contents: &str) -> Result<String, PackageError> {
+    let re = version_regex("zig");
+    let caps = re
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+fn extract_podspec_version(file_contents: &str) -> Result<String, PackageError> {
+    // Versions referencing a constant (e.g. `s.version = MyLib::VERSION`) aren't matched
+    let re = version_regex("ruby_spec_version");
+    let caps = re
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+fn extract_gemspec_version(
+    file_contents: &str,
+    base_dir: &Path,
+    resolve_version_constant: bool,
+) -> Result<String, PackageError> {
+    let re = version_regex("ruby_spec_version");
+    if let Some(caps) = re.captures(file_contents) {
+        return format_captured_version(&caps["version"]);
+    }
+
+    if !resolve_version_constant {
+        return Err(PackageError::NoVersionField);
+    }
+
+    // The version is commonly a `VERSION` constant defined in `lib/<gem name>/version.rb`
+    let constant_re = version_regex("ruby_spec_version_constant");
+    let name_re = version_regex("ruby_spec_name");
+    if !constant_re.is_match(file_contents) {
+        return Err(PackageError::NoVersionField);
+    }
+    let name = name_re
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?["name"]
+        .to_string();
+
+    // Gems named `foo-bar` conventionally live in `lib/foo/bar`
+    let version_rb = [name.clone(), name.replace('-', "/")]
+        .iter()
+        .find_map(|path| utils::read_file(base_dir.join("lib").join(path).join("version.rb")).ok())
+        .ok_or(PackageError::NoVersionField)?;
+
+    let version_re = version_regex("ruby_version_constant");
+    let caps = version_re
+        .captures(&version_rb)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+/// The version `Gemfile.lock` resolved for the gem of a gemspec
+fn get_gemfile_lock_version(gemspec: &str, base_dir: &Path) -> Option<String> {
+    let name = &version_regex("ruby_spec_name").captures(gemspec)?["name"];
+    let lockfile = utils::read_file(base_dir.join("Gemfile.lock")).ok()?;
+    extract_gemfile_lock_version(&lockfile, name)
+}
+
+/// The version of the gem `name` in a `Gemfile.lock`, preferring the `PATH` section
+/// the project itself is listed in over gems installed from a `GEM` source
+fn extract_gemfile_lock_version(lockfile: &str, name: &str) -> Option<String> {
+    let mut section = "";
+    let mut gem_version = None;
+    for line in lockfile.lines() {
+        if !line.starts_with(' ') {
+            section = line.trim();
+            continue;
+        }
+
+        // Specs are indented by four spaces, their dependencies by six
+        let spec = match line.strip_prefix("    ") {
+            Some(spec) if !spec.starts_with(' ') => spec,
+            _ => continue,
+        };
+        let version = spec
+            .strip_prefix(name)
+            .and_then(|spec| spec.strip_prefix(" ("))
+            .and_then(|spec| spec.strip_suffix(')'));
+        match (section, version) {
+            ("PATH", Some(version)) => return Some(format_version(version)),
+            ("GEM", Some(version)) if gem_version.is_none() => {
+                gem_version = Some(format_version(version))
+            }
+            _ => (),
+        }
+    }
+    gem_version
+}
+
+fn extract_chef_version(file_contents: &str) -> Result<String, PackageError> {
+    let caps = version_regex("ruby_version_call")
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+fn extract_brew_formula_version(file_contents: &str) -> Result<String, PackageError> {
+    // Only Ruby files declaring a formula class are Homebrew formulae
+    if !version_regex("brew_formula_class").is_match(file_contents) {
+        return Err(PackageError::NotFound);
+    }
+
+    // Most formulae don't declare a version, letting Homebrew read it from the source URL
+    let caps = version_regex("ruby_version_call")
+        .captures(file_contents)
+        .or_else(|| version_regex("brew_formula_url").captures(file_contents))
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+fn extract_dune_version(file_contents: &str) -> Result<String, PackageError> {
+    let re = version_regex("dune");
+    let caps = re
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+/// Read the version of an opam package, asking `opam show` for it if `resolve_opam_version`
+/// is enabled and the `*.opam` file leaves it to opam-repository
+fn get_opam_version(
+    file_contents: &str,
+    base_dir: &Path,
+    config: &PackageConfig,
+) -> Result<String, PackageError> {
+    match extract_opam_version(file_contents) {
+        Err(PackageError::NoVersionField) if config.resolve_opam_version => {
+            get_opam_cli_version(base_dir, |command, dir| {
+                custom::exec_command(command, None, Some(dir))
+            })
+        }
+        result => result,
+    }
+}
+
+/// Ask the `opam` CLI for the version of the package in `base_dir`. `run` runs a command in a
+/// directory, returning its output.
+fn get_opam_cli_version(
+    base_dir: &Path,
+    run: impl Fn(&str, &Path) -> Option<String>,
+) -> Result<String, PackageError> {
+    let output =
+        run("opam show --field=version .", base_dir).ok_or(PackageError::NoVersionField)?;
+    // Field values are printed as opam strings, e.g. `"1.2.3"`
+    let raw_version = output
+        .lines()
+        .map(|line| line.trim().trim_matches('"'))
+        .find(|line| !line.is_empty())
+        .ok_or(PackageError::NoVersionField)?;
+
+    format_captured_version(raw_version)
+}
+
+fn extract_opam_version(file_contents: &str) -> Result<String, PackageError> {
+    let re = version_regex("opam");
+    let caps = re
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+/// hpack's `package.yaml` declares the version at the top level, like `version: 1.2.3`
+fn extract_hpack_version(file_contents: &str) -> Result<String, PackageError> {
+    extract_yaml_version(file_contents)
+}
+
+/// Cabal field names are case-insensitive, e.g. `Version: 1.2.3`
+fn extract_cabal_version(file_contents: &str) -> Result<String, PackageError> {
+    let re = version_regex("cabal");
+    let caps = re
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+/// The version of the first package listed in the `packages:` of a multi-package
+/// `cabal.project`, as a directory, a `.cabal` file or a glob matching either
+fn get_cabal_project_version(file_contents: &str, base_dir: &Path) -> Result<String, PackageError> {
+    let caps = version_regex("cabal_project_packages")
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+    let first_package = caps["packages"]
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .find(|package| !package.is_empty())
+        .ok_or(PackageError::NoVersionField)?;
+
+    let pattern =
+        Path::new(&glob::Pattern::escape(&base_dir.to_string_lossy())).join(first_package);
+    let mut matches: Vec<PathBuf> = glob::glob(&pattern.to_string_lossy())
+        .map_err(|_| PackageError::ParseFailed)?
+        .filter_map(Result::ok)
+        .collect();
+    matches.sort();
+    let cabal_file = matches
+        .into_iter()
+        .find_map(|path| {
+            if path.is_dir() {
+                find_manifest_with_extension(&DirFiles::new(&path), "cabal").ok()?
+            } else {
+                Some(path).filter(|path| path.extension() == Some(OsStr::new("cabal")))
+            }
+        })
+        .ok_or(PackageError::NoVersionField)?;
+
+    extract_cabal_version(&utils::read_file(cabal_file)?)
+}
+
+/// Whether a Dart `pubspec.yaml` opts out of publishing with `publish_to: none`
+fn is_unpublishable_pubspec(file_contents: &str) -> bool {
+    YamlLoader::load_from_str(file_contents)
+        .ok()
+        .and_then(|docs| docs.into_iter().next())
+        .is_some_and(|pubspec| pubspec["publish_to"].as_str() == Some("none"))
+}
+
+fn extract_dotnet_version(file_contents: &str) -> Result<String, PackageError> {
+    let property = |name| {
+        version_regex(name)
+            .captures(file_contents)
+            .map(|caps| {
+                resolve_property_references(&caps[1], "msbuild_property_ref", |name| {
+                    find_xml_property(file_contents, name)
+                })
+            })
+            .transpose()
+    };
+
+    if let Some(version) = property("csproj")? {
+        return format_captured_version(&version);
+    }
+
+    // Without a `<Version>`, MSBuild composes it as `$(VersionPrefix)-$(VersionSuffix)`
+    let prefix = property("csproj_prefix")?.ok_or(PackageError::NoVersionField)?;
+    let raw_version = match property("csproj_suffix")? {
+        Some(suffix) => format!("{}-{}", prefix, suffix),
+        None => prefix,
+    };
+
+    format_captured_version(&raw_version)
+}
+
+fn extract_nuspec_version(file_contents: &str) -> Result<String, PackageError> {
+    let caps = version_regex("nuspec")
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+/// The `CFBundleShortVersionString` of an XML `Info.plist`
+fn extract_plist_version(file_contents: &str) -> Result<String, PackageError> {
+    let caps = version_regex("plist")
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+/// SwiftPM manifests don't declare a version, packages are versioned by their git tags
+fn get_swift_version(base_dir: &Path, config: &PackageConfig) -> Result<String, PackageError> {
+    if !config.resolve_git_version {
+        return Err(PackageError::NoVersionField);
+    }
+
+    let manifest = ManifestKind::Swift
+        .locate(&DirFiles::new(base_dir))?
+        .ok_or(PackageError::NotFound)?;
+    cached_version(&manifest, config, || {
+        get_git_describe_version(base_dir, config.require_clean_repo, config.describe_format)
+    })
+    .ok_or(PackageError::NoVersionField)
+}
+
+/// The version of the `module()` declared by a Bzlmod `MODULE.bazel`
+fn extract_bazel_module_version(file_contents: &str) -> Result<String, PackageError> {
+    let caps = version_regex("bazel_module")
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+/// The `version` attribute of a packaging rule in a `BUILD` file, like `pkg_deb` or `py_wheel`,
+/// skipping the versions of other calls, like `maven.artifact(version = "31.1-jre")`. Most
+/// `BUILD` files don't package anything, so they don't hide the manifests probed after them.
+fn extract_bazel_build_version(file_contents: &str) -> Result<String, PackageError> {
+    let caps = version_regex("bazel_rule_version")
+        .captures(file_contents)
+        .ok_or(PackageError::NotFound)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+/// The `Version:` tag of an RPM `.spec` file, unless it is computed by macros like `%{ver}`.
+/// Other `.spec` files, like PyInstaller's, have no `Version:` tag and are skipped.
+fn extract_rpm_spec_version(file_contents: &str) -> Result<String, PackageError> {
+    let caps = version_regex("rpm_spec")
+        .captures(file_contents)
+        .ok_or(PackageError::NotFound)?;
+    if caps["version"].contains('%') {
+        return Err(PackageError::NoVersionField);
+    }
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+/// The version of the latest entry in `debian/changelog`, optionally without the Debian
+/// revision that counts the packaging changes made to the same upstream version
+fn extract_debian_changelog_version(
+    file_contents: &str,
+    strip_revision: bool,
+) -> Result<String, PackageError> {
+    let caps = version_regex("debian_changelog")
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+    let version = &caps["version"];
+    let version = match version.rfind('-') {
+        Some(index) if strip_revision => &version[..index],
+        _ => version,
+    };
+
+    let formatted_version = format_captured_version(version)?;
+    Ok(formatted_version)
+}
+
+/// The `version` of a LuaRocks `.rockspec`, including its rockspec revision like `1.0-1`
+fn extract_rockspec_version(file_contents: &str) -> Result<String, PackageError> {
+    let caps = version_regex("rockspec")
+        .captures(file_contents)
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(&caps["version"])?;
+    Ok(formatted_version)
+}
+
+/// The version written on the first non-empty line of a plain version file like `VERSION`
+fn extract_plain_version(file_contents: &str) -> Result<String, PackageError> {
+    let version = file_contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .ok_or(PackageError::NoVersionField)?;
+
+    let formatted_version = format_captured_version(version)?;
+    Ok(formatted_version)
+}
+
+/// The `versionNumber` of the first package directory of a Salesforce DX project, without
+/// the `NEXT` or `LATEST` build number the version is completed with when it is packaged
+fn extract_sfdx_version(file_contents: &str) -> Result<String, PackageError> {
+    let sfdx_project: json::Value =
+        json::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+    let raw_version = sfdx_project
+        .get("packageDirectories")
+        .and_then(json::Value::as_array)
+        .and_then(|directories| {
+            directories
+                .iter()
+                .find_map(|directory| directory.get("versionNumber")?.as_str())
+        })
+        .ok_or(PackageError::NoVersionField)?;
+    let raw_version = raw_version
+        .strip_suffix(".NEXT")
+        .or_else(|| raw_version.strip_suffix(".LATEST"))
+        .unwrap_or(raw_version);
+
+    let formatted_version = format_version(raw_version);
+    Ok(formatted_version)
+}
+
+/// The top-level `version` of the `CITATION.cff` of research software. It is optional, so
+/// citations without one don't stop the search for other manifests.
+fn extract_citation_cff_version(file_contents: &str) -> Result<String, PackageError> {
+    match extract_yaml_version(file_contents) {
+        Err(PackageError::NoVersionField) => Err(PackageError::NotFound),
+        result => result,
+    }
+}
+
+/// The `org.opencontainers.image.version` label of a `Dockerfile`, or its `version` label.
+/// Most Dockerfiles have neither, so they don't stop the search for other manifests.
+fn extract_dockerfile_version(file_contents: &str) -> Result<String, PackageError> {
+    let instructions = file_contents.replace("\\\r\n", " ").replace("\\\n", " ");
+    let labels: HashMap<&str, &str> = version_regex("dockerfile_label")
+        .captures_iter(&instructions)
+        .flat_map(|label| {
+            let labels = label.name("labels").map_or("", |labels| labels.as_str());
+            version_regex("dockerfile_label_pair").captures_iter(labels)
+        })
+        .filter_map(|pair| {
+            let value = pair
+                .name("double")
+                .or_else(|| pair.name("single"))
+                .or_else(|| pair.name("bare"))?;
+            Some((pair.name("key")?.as_str(), value.as_str()))
+        })
+        .collect();
+
+    let raw_version = labels
+        .get("org.opencontainers.image.version")
+        .or_else(|| labels.get("version"))
+        .ok_or(PackageError::NotFound)?;
+
+    let formatted_version = format_captured_version(raw_version)?;
+    Ok(formatted_version)
+}
+
+/// The version of the toolchain a package pins, shown next to its version
+fn get_tool_version(manifest: ManifestKind, base_dir: &Path) -> Option<String> {
+    match manifest {
+        // Like the .NET CLI, use the `global.json` closest to the project
+        ManifestKind::Dotnet => base_dir
+            .ancestors()
+            .find_map(|dir| utils::read_file(dir.join("global.json")).ok())
+            .and_then(|global_json| extract_dotnet_sdk_version(&global_json)),
+        ManifestKind::Npm => utils::read_file(base_dir.join("package.json"))
+            .ok()
+            .and_then(|package_json| extract_node_engine(&package_json)),
+        _ => None,
+    }
+}
+
+/// The Node.js versions a package supports, from the `engines.node` of its `package.json`
+fn extract_node_engine(file_contents: &str) -> Option<String> {
+    let package_json: json::Value = json::from_str(file_contents).ok()?;
+    let constraint = package_json.pointer("/engines/node")?.as_str()?.trim();
+    if constraint.is_empty() {
+        return None;
+    }
+
+    Some(format!("node {}", sanitize_version(constraint)))
+}
+
+fn extract_dotnet_sdk_version(file_contents: &str) -> Option<String> {
+    let global_json: json::Value = json::from_str(file_contents).ok()?;
+    let raw_version = global_json.pointer("/sdk/version")?.as_str()?;
+
+    Some(format!(".NET SDK {}", format_version(raw_version)))
+}
+
+/// Read the top-level `version` of a YAML file
+fn extract_yaml_version(file_contents: &str) -> Result<String, PackageError> {
+    let yaml_docs =
+        YamlLoader::load_from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+    let raw_version = match yaml_docs.first().map(|doc| &doc["version"]) {
+        // Unquoted versions like `1.2` are parsed as numbers
+        Some(Yaml::String(version)) | Some(Yaml::Real(version)) => version.to_string(),
+        Some(Yaml::Integer(version)) => version.to_string(),
+        _ => return Err(PackageError::NoVersionField),
+    };
+
+    let formatted_version = format_version(&raw_version);
+    Ok(formatted_version)
+}
+
+/// How the version of a manifest registered with `detect_*` is read
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CustomFormat {
+    Toml,
+    Json,
+    Yaml,
+    Regex,
+}
+
+/// The keys tried, in order, for the `detect_*` manifests configured without one
+const DEFAULT_VERSION_KEYS: &[&str] = &["version", "package.version"];
+
+/// Read the version at the first of the `DEFAULT_VERSION_KEYS` a manifest has
+fn extract_default_custom_version(
+    format: CustomFormat,
+    file_contents: &str,
+) -> Result<String, PackageError> {
+    DEFAULT_VERSION_KEYS
+        .iter()
+        .map(|key| extract_custom_version(format, file_contents, key))
+        .find(|version| *version != Err(PackageError::NoVersionField))
+        .unwrap_or(Err(PackageError::NoVersionField))
+}
+
+/// Read the version at the dotted `key` (e.g. `package.version`) of a manifest,
+/// or from the first group matched by a regex
+fn extract_custom_version(
+    format: CustomFormat,
+    file_contents: &str,
+    key: &str,
+) -> Result<String, PackageError> {
+    let raw_version = match format {
+        CustomFormat::Toml => {
+            let manifest: toml::Value =
+                toml::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+            key.split('.')
+                .try_fold(&manifest, |value, key| value.get(key))
+                .and_then(|version| match version {
+                    toml::Value::String(version) => Some(version.to_string()),
+                    toml::Value::Integer(_) | toml::Value::Float(_) => Some(version.to_string()),
+                    _ => None,
+                })
+        }
+        CustomFormat::Json => {
+            let manifest: json::Value =
+                json::from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+            // Keys starting with `/` are JSON pointers, e.g. `/targets/build/options/version`
+            let version = if key.starts_with('/') {
+                manifest.pointer(key)
+            } else {
+                key.split('.')
+                    .try_fold(&manifest, |value, key| value.get(key))
+            };
+            version.and_then(|version| match version {
+                json::Value::String(version) => Some(version.to_string()),
+                json::Value::Number(version) => Some(version.to_string()),
+                _ => None,
+            })
+        }
+        CustomFormat::Yaml => {
+            let yaml_docs =
+                YamlLoader::load_from_str(file_contents).map_err(|_| PackageError::ParseFailed)?;
+            let version = key.split('.').fold(
+                yaml_docs.first().unwrap_or(&Yaml::BadValue),
+                |value, key| &value[key],
+            );
+            match version {
+                Yaml::String(version) | Yaml::Real(version) => Some(version.to_string()),
+                Yaml::Integer(version) => Some(version.to_string()),
+                _ => None,
+            }
+        }
+        // Unlike keys, patterns can capture anything, so captures are checked like built-in ones
+        CustomFormat::Regex => {
+            let re = Regex::new(key).map_err(|_| PackageError::ParseFailed)?;
+            let caps = re
+                .captures(file_contents)
+                .ok_or(PackageError::NoVersionField)?;
+            let raw_version = caps
+                .name("version")
+                .or_else(|| caps.get(1))
+                .ok_or(PackageError::NoVersionField)?;
+            return format_captured_version(raw_version.as_str());
+        }
+    };
+
+    let formatted_version = format_version(&raw_version.ok_or(PackageError::NoVersionField)?);
+    Ok(formatted_version)
+}
+
+fn extract_cargo_kind(file_contents: &str, base_dir: &Path) -> Option<PackageKind> {
+    let cargo_toml: toml::Value = toml::from_str(file_contents).ok()?;
+
+    // Mirror cargo's target auto-discovery when no targets are declared explicitly
+    if cargo_toml.get("lib").is_some() || base_dir.join("src/lib.rs").is_file() {
+        Some(PackageKind::Lib)
+    } else if cargo_toml.get("bin").is_some() || base_dir.join("src/main.rs").is_file() {
+        Some(PackageKind::Bin)
+    } else {
+        None
+    }
+}
+
+fn extract_package_kind(file_contents: &str) -> Option<PackageKind> {
+    let package_json: json::Value = json::from_str(file_contents).ok()?;
+
+    if package_json.get("bin").is_some() {
+        Some(PackageKind::Bin)
+    } else {
+        None
+    }
+}
+
+fn get_package_kind(base_dir: &Path) -> Option<PackageKind> {
+    if let Ok(cargo_toml) = utils::read_file(base_dir.join("Cargo.toml")) {
+        extract_cargo_kind(&cargo_toml, base_dir)
+    } else if let Ok(package_json) = utils::read_file(base_dir.join("package.json")) {
+        extract_package_kind(&package_json)
+    } else {
+        None
+    }
+}
+
+/// Pick the symbol configured in `symbol_variants` for the kind of package in `base_dir`,
+/// falling back to the `default` variant
+fn get_symbol_variant<'a>(base_dir: &Path, config: &PackageConfig<'a>) -> Option<&'a str> {
+    if config.symbol_variants.is_empty() {
+        return None;
+    }
+
+    get_package_kind(base_dir)
+        .and_then(|kind| config.symbol_variants.get(kind.as_str()))
+        .or_else(|| config.symbol_variants.get("default"))
+        .copied()
+}
+
+/// Read a manifest from `base_dir`, returning `None` if it doesn't exist
+fn read_manifest(base_dir: &Path, file_name: &str) -> Result<Option<String>, PackageError> {
+    match utils::read_file(base_dir.join(file_name)) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// The path of `file_name` in `base_dir`, if it exists
+fn find_manifest(base_dir: &Path, file_name: &str) -> Option<PathBuf> {
+    Some(base_dir.join(file_name)).filter(|manifest| manifest.is_file())
+}
+
+/// The files of a directory, listed once for all the manifests looked up in it by extension or
+/// pattern rather than by name
+struct DirFiles<'a> {
+    dir: &'a Path,
+    files: OnceCell<BTreeSet<PathBuf>>,
+}
+
+impl<'a> DirFiles<'a> {
+    fn new(dir: &'a Path) -> Self {
+        DirFiles {
+            dir,
+            files: OnceCell::new(),
+        }
+    }
+
+    /// The files in the directory, in alphabetical order, listed the first time they're needed
+    fn list(&self) -> Result<&BTreeSet<PathBuf>, PackageError> {
+        if let Some(files) = self.files.get() {
+            return Ok(files);
+        }
+
+        let files = fs::read_dir(self.dir)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect();
+        Ok(self.files.get_or_init(|| files))
+    }
+}
+
+/// The first of `files` with `extension`, in alphabetical order
+fn find_manifest_with_extension(
+    files: &DirFiles,
+    extension: &str,
+) -> Result<Option<PathBuf>, PackageError> {
+    find_first_manifest(files, |path| {
+        path.extension() == Some(OsStr::new(extension))
+    })
+}
+
+/// The first of `files` whose name matches the glob `pattern`, in alphabetical order
+fn find_manifest_matching(
+    files: &DirFiles,
+    pattern: &str,
+) -> Result<Option<PathBuf>, PackageError> {
+    let pattern = glob::Pattern::new(pattern).map_err(|_| PackageError::NotFound)?;
+    find_first_manifest(files, |path| {
+        path.file_name()
+            .and_then(OsStr::to_str)
+            .is_some_and(|file_name| pattern.matches(file_name))
+    })
+}
+
+/// The one of `files` with `extension` whose name carries the highest version, like the
+/// `starship-1.1-1.rockspec` LuaRocks keeps next to `starship-1.0-1.rockspec`
+fn find_newest_manifest_with_extension(
+    files: &DirFiles,
+    extension: &str,
+) -> Result<Option<PathBuf>, PackageError> {
+    // The files are sorted by name, which keeps the choice between equal versions stable
+    Ok(files
+        .list()?
+        .iter()
+        .filter(|path| path.extension() == Some(OsStr::new(extension)))
+        .max_by_key(|path| file_name_version(path))
+        .cloned())
+}
+
+/// The numeric components of the `<version>-<revision>` a manifest's file name ends with,
+/// e.g. `[1, 10, 0, 2]` for `starship-1.10.0-2.rockspec`. Components that aren't numbers,
+/// like `scm` or `rc1`, count as 0.
+fn file_name_version(path: &Path) -> Vec<u64> {
+    let stem = path.file_stem().and_then(OsStr::to_str).unwrap_or_default();
+    let mut parts = stem.rsplitn(3, '-');
+    let revision = parts.next().unwrap_or_default();
+    let version = parts.next().unwrap_or_default();
+
+    version
+        .split('.')
+        .chain(std::iter::once(revision))
+        .map(|component| component.parse().unwrap_or(0))
+        .collect()
+}
+
+fn find_first_manifest<P>(files: &DirFiles, is_manifest: P) -> Result<Option<PathBuf>, PackageError>
+where
+    P: Fn(&Path) -> bool,
+{
+    Ok(files.list()?.iter().find(|path| is_manifest(path)).cloned())
+}
+
+/// Reads the version of a package from one kind of manifest
+trait ManifestExtractor {
+    /// The manifest this extractor reads among the `files` of a directory, if there is one
+    fn detect(&self, files: &DirFiles) -> Result<Option<PathBuf>, PackageError>;
+
+    /// The version of the package in `dir` read from `file`, the manifest found by `detect`,
+    /// or `PackageError::NotFound` if it turns out not to be one this extractor reads, so that
+    /// the next one is tried
+    fn extract(
+        &self,
+        file: PathBuf,
+        dir: &Path,
+        config: &PackageConfig,
+    ) -> Result<PackageVersion, PackageError>;
+}
+
+impl ManifestExtractor for ManifestKind {
+    fn detect(&self, files: &DirFiles) -> Result<Option<PathBuf>, PackageError> {
+        self.locate(files)
+    }
+
+    fn extract(
+        &self,
+        file: PathBuf,
+        dir: &Path,
+        config: &PackageConfig,
+    ) -> Result<PackageVersion, PackageError> {
+        let file_contents = match utils::read_file(&file) {
+            Ok(file_contents) => file_contents,
+            // Binary manifests, like binary property lists, have no version to read
+            Err(ref error) if error.kind() == io::ErrorKind::InvalidData => {
+                return Err(PackageError::NoVersionField)
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        let version = self.extract_version(&file_contents, dir, config)?;
+        Ok(PackageVersion {
+            version,
+            manifest: *self,
+            name: self.extract_name(&file_contents),
+            dir: dir.to_path_buf(),
+            file,
+        })
+    }
+}
+
+/// The extractors of the built-in manifests, in the order they are probed
+fn manifest_extractors() -> Vec<Box<dyn ManifestExtractor>> {
+    supported_manifests()
+        .iter()
+        .map(|&manifest| Box::new(manifest) as Box<dyn ManifestExtractor>)
+        .collect()
+}
+
+fn get_package_version<'a>(
+    base_dir: &Path,
+    config: &'a PackageConfig<'a>,
+) -> Result<PackageVersion, PackageError> {
+    get_package_version_from(base_dir, config, &manifest_extractors())
+}
+
+/// The version of the package in `base_dir` from the first of `extractors` that reads one,
+/// or else from the manifests registered in the config
+fn get_package_version_from<'a>(
+    base_dir: &Path,
+    config: &'a PackageConfig<'a>,
+    extractors: &[Box<dyn ManifestExtractor>],
+) -> Result<PackageVersion, PackageError> {
+    // Locating some manifests lists the directory, so it is only listed once for all of them
+    let files = DirFiles::new(base_dir);
+    for extractor in extractors {
+        let file = match extractor.detect(&files)? {
+            Some(file) => file,
+            None => continue,
+        };
+
+        match extractor.extract(file, base_dir, config) {
+            // The file exists but isn't a manifest of this kind, e.g. a generic `manifest.json`
+            Err(PackageError::NotFound) => continue,
+            result => return result,
+        }
+    }
+
+    for file_name in &config.extra_yaml_manifests {
+        if let Some(file_contents) = read_manifest(base_dir, file_name)? {
+            return ManifestKind::Yaml
+                .extract_version(&file_contents, base_dir, config)
+                .map(|version| PackageVersion {
+                    version,
+                    manifest: ManifestKind::Yaml,
+                    name: None,
+                    dir: base_dir.to_path_buf(),
+                    file: base_dir.join(file_name),
+                });
+        }
+    }
+
+    let key_manifests = |manifests: &'a [CustomManifest<'a>], format| {
+        manifests
+            .iter()
+            .map(move |manifest| (format, manifest.file, manifest.key))
+    };
+    let custom_manifests = key_manifests(&config.detect_toml, CustomFormat::Toml)
+        .chain(key_manifests(&config.detect_json, CustomFormat::Json))
+        .chain(key_manifests(&config.detect_yaml, CustomFormat::Yaml))
+        .chain(
+            config
+                .detect_regex
+                .iter()
+                .map(|manifest| (CustomFormat::Regex, manifest.file, Some(manifest.pattern))),
+        );
+    for (format, file_name, key) in custom_manifests {
+        if let Some(file_contents) = read_manifest(base_dir, file_name)? {
+            let version = match key {
+                Some(key) => extract_custom_version(format, &file_contents, key),
+                None => extract_default_custom_version(format, &file_contents),
+            };
+            return version.map(|version| PackageVersion {
+                version,
+                manifest: ManifestKind::Custom,
+                name: None,
+                dir: base_dir.to_path_buf(),
+                file: base_dir.join(file_name),
+            });
+        }
+    }
+
+    for manifest in &config.detect_extensions {
+        let file = match find_manifest_with_extension(&files, manifest.extension)? {
+            Some(file) => file,
+            None => continue,
+        };
+        let file_contents = utils::read_file(&file)?;
+        let format = match manifest.parser {
+            ManifestParser::Toml => CustomFormat::Toml,
+            ManifestParser::Json => CustomFormat::Json,
+            ManifestParser::Yaml => CustomFormat::Yaml,
+            ManifestParser::Regex => CustomFormat::Regex,
+        };
+        let version = match manifest.key {
+            Some(key) => extract_custom_version(format, &file_contents, key),
+            None => extract_default_custom_version(format, &file_contents),
+        };
+        return version.map(|version| PackageVersion {
+            version,
+            manifest: ManifestKind::Custom,
+            name: None,
+            dir: base_dir.to_path_buf(),
+            file,
+        });
+    }
+
+    for file_name in &config.version_file_names {
+        if let Some(file_contents) = read_manifest(base_dir, file_name)? {
+            return ManifestKind::VersionFile
+                .extract_version(&file_contents, base_dir, config)
+                .map(|version| PackageVersion {
+                    version,
+                    manifest: ManifestKind::VersionFile,
+                    name: None,
+                    dir: base_dir.to_path_buf(),
+                    file: base_dir.join(file_name),
+                });
+        }
+    }
+
+    Err(PackageError::NotFound)
+}
+
+/// Whether `dir` matches any of the `ignore_paths` glob patterns
+fn is_ignored_path(dir: &Path, ignore_paths: &[&str]) -> bool {
+    ignore_paths
+        .iter()
+        .any(|pattern| match glob::Pattern::new(pattern) {
+            Ok(pattern) => pattern.matches_path(dir),
+            Err(error) => {
+                log::warn!("Invalid package ignore path {:?}: {}", pattern, error);
+                false
+            }
+        })
+}
+
+/// Look for a package version in the current directory, then in its parents up to
+/// `max_search_depth` levels above it, stopping at the root of the enclosing repository
+/// or when a directory is reached a second time through symlinks
+fn find_package_version(
+    context: &Context,
+    config: &PackageConfig,
+) -> Result<PackageVersion, PackageError> {
+    let repo_root = context
+        .get_repo()
+        .ok()
+        .and_then(|repo| repo.root.as_deref());
+    // `current_dir` follows symlinks in `$PWD`, while git reports the physical path of the root
+    let canonical_repo_root =
+        repo_root.map(|root| root.canonicalize().unwrap_or_else(|_| root.to_path_buf()));
+    let mut visited = HashSet::new();
+
+    let start_dir = match (config.context_root, repo_root) {
+        (ContextRoot::GitRoot, Some(repo_root)) => repo_root,
+        _ => context.current_dir.as_path(),
+    };
+    for dir in start_dir.ancestors().take(config.max_search_depth + 1) {
+        // A symlink can make a directory its own parent, e.g. `a/loop -> a`, there's no need
+        // to probe it again
+        let canonical_dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let is_repo_root = canonical_repo_root.as_ref() == Some(&canonical_dir);
+        if !visited.insert(canonical_dir) {
+            log::debug!("Skipping {:?}, which was already searched", dir);
+            continue;
+        }
+
+        match get_package_version(dir, config) {
+            Err(PackageError::NotFound) if !is_repo_root => continue,
+            result => return result,
+        }
+    }
+
+    Err(PackageError::NotFound)
+}
+
+/// Replace a placeholder `version` like `0.0.0` by the git tag of `dir`, cached for `manifest`
+/// when the version was read from one. Placeholders are hidden unless `resolve_git_version`.
+fn resolve_placeholder_version(
+    version: String,
+    dir: &Path,
+    manifest: Option<&Path>,
+    config: &PackageConfig,
+) -> Option<String> {
+    if !is_placeholder_version(&version, &config.placeholder_versions) {
+        return Some(version);
+    }
+    if !config.resolve_git_version {
+        log::debug!("Hiding placeholder package version {}", version);
+        return None;
+    }
+    log::debug!(
+        "Replacing placeholder package version {} by its git tag",
+        version
+    );
+    let describe =
+        || get_git_describe_version(dir, config.require_clean_repo, config.describe_format);
+    match manifest {
+        Some(manifest) => cached_version(manifest, config, describe),
+        None => describe(),
+    }
+}
+
+/// The hidden subcommand resolving the package version of `--path` ahead of the prompt
+pub const WARM_CACHE_SUBCOMMAND: &str = "package-warm-cache";
+
+/// Resolve the package version from the directory of `context` ahead of time, e.g. from a
+/// background job a shell starts when changing directories, so that the versions resolved from
+/// git are already cached on disk when the prompt is rendered
+pub fn warm_package_cache(context: &Context) -> Option<String> {
+    let config = PackageConfig::try_load(context.config.get_module_config("package"));
+    match find_package_version(context, &config) {
+        Ok(package) => {
+            resolve_placeholder_version(package.version, &package.dir, Some(&package.file), &config)
+        }
+        Err(error) => {
+            log::debug!(
+                "Unable to warm the package cache for {:?}: {:?}",
+                context.current_dir,
+                error
+            );
+            None
+        }
+    }
+}
+
+/// Get a version for projects without a supported manifest, from the user's `version_command`
+/// or, as a last resort, from a README badge
+fn get_fallback_version(base_dir: &Path, config: &PackageConfig) -> Option<String> {
+    config
+        .version_command
+        .and_then(|command| get_command_version(command, base_dir))
+        .or_else(|| {
+            if config.readme_badge_fallback {
+                get_readme_badge_version(base_dir)
+            } else {
+                None
+            }
+        })
+        .or_else(|| config.fallback_version.map(format_version))
+}
+
+/// Run the user's `version_command` in `base_dir`
+fn get_command_version(command: &str, base_dir: &Path) -> Option<String> {
+    let output = custom::exec_command(command, None, Some(base_dir))?;
+    let version = output.trim();
+    if version.is_empty() {
+        return None;
+    }
+
+    Some(format_version(version))
+}
+
+/// Heuristically read the version from a shields.io badge in `README.md`,
+/// e.g. `![version](https://img.shields.io/badge/version-1.2.3-blue)`
+fn get_readme_badge_version(base_dir: &Path) -> Option<String> {
+    let readme = utils::read_file(base_dir.join("README.md")).ok()?;
+    let caps = version_regex("readme_badge").captures(&readme)?;
+
+    // shields.io escapes dashes in badge text by doubling them
+    let raw_version = caps["version"].replace("--", "-");
+    Some(format_version(&raw_version))
+}
+
+/// How long to wait for the registry before giving up on an update check. The check runs in
+/// the background, so it doesn't hold up the prompt.
+#[cfg(feature = "http")]
+const UPDATE_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The hidden subcommand checking the latest release of the package in `--path`
+pub const UPDATE_CHECK_SUBCOMMAND: &str = "package-update-check";
+
+/// The registry API endpoint describing the latest release of `package`
+fn get_latest_version_url(package: &PackageVersion, config: &PackageConfig) -> Option<String> {
+    let name = package.name.as_deref()?;
+    let registry = package
+        .manifest
+        .ecosystem()
+        .and_then(|ecosystem| config.registry_overrides.get(ecosystem).copied());
+    package.manifest.latest_version_url(name, registry)
+}
+
+/// The latest release of a package cached in `cache_dir` for its `latest_version_url`. If it
+/// wasn't checked in the `max_age` before `now`, `refresh` is started to check it again in the
+/// background and the previous check is used in the meantime. The time of the check is cached
+/// before `refresh` starts, so that checks that fail, e.g. offline, aren't retried every prompt.
+fn get_latest_version<F>(
+    url: &str,
+    cache_dir: &Path,
+    max_age: Duration,
+    now: SystemTime,
+    refresh: F,
+) -> Option<String>
+where
+    F: FnOnce(),
+{
+    // Keyed by URL, so that packages on different registries don't share a cached release
+    let cache_file = cache_dir.join(cache_file_name(url));
+    let cached = read_cached_latest_version(&cache_file, url);
+    let is_fresh = cached.as_ref().is_some_and(|(checked_at, _)| {
+        now.duration_since(*checked_at)
+            .is_ok_and(|age| age < max_age)
+    });
+    let version = cached.map(|(_, version)| version);
+
+    if is_fresh {
+        log::trace!("Using cached latest version from {}", url);
+    } else {
+        let version = version.as_deref().unwrap_or_default();
+        if let Err(error) = write_cached_latest_version(cache_dir, &cache_file, url, now, version) {
+            log::debug!("Unable to cache latest version from {}: {}", url, error);
+        }
+        refresh();
+    }
+    version.filter(|version| !version.is_empty())
+}
+
+/// Fetch the latest release of a package with `fetch` from its `latest_version_url`, and cache
+/// it in `cache_dir` as checked at `now`
+fn update_latest_version<F>(
+    manifest: ManifestKind,
+    url: &str,
+    cache_dir: &Path,
+    now: SystemTime,
+    fetch: F,
+) -> Option<String>
+where
+    F: FnOnce(&str) -> Option<String>,
+{
+    let response = fetch(url)?;
+    let version = manifest.parse_latest_version(&response)?;
+    let cache_file = cache_dir.join(cache_file_name(url));
+    if let Err(error) = write_cached_latest_version(cache_dir, &cache_file, url, now, &version) {
+        log::debug!("Unable to cache latest version from {}: {}", url, error);
+    }
+    Some(version)
+}
+
+fn write_cached_latest_version(
+    cache_dir: &Path,
+    cache_file: &Path,
+    url: &str,
+    checked_at: SystemTime,
+    version: &str,
+) -> io::Result<()> {
+    let checked_at = checked_at
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    fs::create_dir_all(cache_dir)?;
+    fs::write(cache_file, format!("{}\n{}\n{}", url, checked_at, version))
+}
+
+/// The time a cached latest release was checked, in milliseconds since the Unix epoch on
+/// the second line of the cache file, and the release itself on the third. The release is
+/// empty while the first check is running, or if it failed. The first line holds the `url`
+/// the release was checked at, since URLs that only differ in punctuation share a cache file.
+fn read_cached_latest_version(cache_file: &Path, url: &str) -> Option<(SystemTime, String)> {
+    let contents = utils::read_file(cache_file).ok()?;
+    let contents = contents.strip_prefix(url)?.strip_prefix('\n')?;
+    let (checked_at, version) = contents.split_once('\n')?;
+    let checked_at = SystemTime::UNIX_EPOCH + Duration::from_millis(checked_at.parse().ok()?);
+    Some((checked_at, version.to_string()))
+}
+
+/// Check the latest release of the package in `dir` from a detached starship process, which
+/// keeps running after the prompt is printed
+fn start_update_check(dir: &Path) {
+    let started = std::env::current_exe().and_then(|starship| {
+        Command::new(starship)
+            .arg(UPDATE_CHECK_SUBCOMMAND)
+            .arg("--path")
+            .arg(dir)
+            // The shell waits for the output of the prompt to be closed
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+    });
+    if let Err(error) = started {
+        log::debug!("Unable to start the package update check: {}", error);
+    }
+}
+
+/// Check the latest release of the package found from the directory of `context` and cache
+/// it, for the `package-update-check` subcommand started by `start_update_check`
+pub fn check_for_update(context: &Context) {
+    let config = PackageConfig::try_load(context.config.get_module_config("package"));
+    let latest_version = find_package_version(context, &config)
+        .ok()
+        .and_then(|package| {
+            let url = get_latest_version_url(&package, &config)?;
+            update_latest_version(
+                package.manifest,
+                &url,
+                &package_cache_dir()?,
+                SystemTime::now(),
+                fetch_url,
+            )
+        });
+    log::debug!("Latest package version: {:?}", latest_version);
+}
+
+#[cfg(feature = "http")]
+fn fetch_url(url: &str) -> Option<String> {
+    let response = attohttpc::get(url)
+        .header(
+            "User-Agent",
+            "starship (https://github.com/starship/starship)",
+        )
+        .timeout(UPDATE_CHECK_TIMEOUT)
+        .send()
+        .ok()?;
+    if !response.is_success() {
+        log::debug!("Registry responded to {} with {}", url, response.status());
+        return None;
+    }
+    response.text().ok()
+}
+
+#[cfg(not(feature = "http"))]
+fn fetch_url(_url: &str) -> Option<String> {
+    None
+}
+
+/// Whether `version` comes before `latest` when compared numerically,
+/// component by component, e.g. `v1.9.0` before `v1.10.0`. Missing components count as 0,
+/// and pre-releases like `v1.10.0-rc.1` come before their release.
+fn is_version_behind(version: &str, latest: &str) -> bool {
+    fn parse(version: &str) -> (Vec<u64>, Option<&str>) {
+        let version = version.trim_start_matches('v');
+        let version = version.split('+').next().unwrap_or_default();
+        let (core, pre_release) = match version.split_once('-') {
+            Some((core, pre_release)) => (core, Some(pre_release)),
+            None => (version, None),
+        };
+        let components = core
+            .split('.')
+            .map(|component| component.parse().unwrap_or(0))
+            .collect();
+        (components, pre_release)
+    }
+
+    // Numeric identifiers come before alphanumeric ones, like in semver
+    fn pre_release_identifiers(pre_release: &str) -> Vec<(bool, u64, &str)> {
+        pre_release
+            .split('.')
+            .map(|identifier| match identifier.parse() {
+                Ok(number) => (false, number, ""),
+                Err(_) => (true, 0, identifier),
+            })
+            .collect()
+    }
+
+    let (mut version_components, version_pre_release) = parse(version);
+    let (mut latest_components, latest_pre_release) = parse(latest);
+    let len = version_components.len().max(latest_components.len());
+    version_components.resize(len, 0);
+    latest_components.resize(len, 0);
+
+    match version_components.cmp(&latest_components) {
+        Ordering::Less => true,
+        Ordering::Greater => false,
+        Ordering::Equal => match (version_pre_release, latest_pre_release) {
+            (Some(_), None) => true,
+            (Some(version_pre_release), Some(latest_pre_release)) => {
+                pre_release_identifiers(version_pre_release)
+                    < pre_release_identifiers(latest_pre_release)
+            }
+            _ => false,
+        },
+    }
+}
+
+/// The other manifests next to the one `package` was read from that declare a different
+/// version, like a `package.json` left behind by a `Cargo.toml` bump in a WASM project.
+/// Only the versions the manifests declare are compared, none resolved from git, the opam CLI
+/// or lock files, so that every prompt doesn't run them for all the manifests.
+fn find_version_mismatches(package: &PackageVersion, config: &PackageConfig) -> Vec<String> {
+    let config = PackageConfig {
+        resolve_gem_version_constant: false,
+        resolve_gemfile_lock: false,
+        resolve_git_version: false,
+        resolve_opam_version: false,
+        ..config.clone()
+    };
+    let files = DirFiles::new(&package.dir);
+    supported_manifests()
+        .iter()
+        .filter(|&&manifest| manifest != package.manifest)
+        .filter_map(|manifest| {
+            let file = manifest.detect(&files).ok()??;
+            manifest.extract(file, &package.dir, &config).ok()
+        })
+        .filter(|other| other.version != package.version)
+        .map(|other| format!("{} in {}", other.version, other.file.display()))
+        .collect()
+}
+
+/// The file release engineers pin the expected version of a package in, next to its manifest
+const VERSION_LOCK_FILE: &str = ".version-lock";
+
+/// The version pinned in the `.version-lock` in `dir`, if there is one
+fn get_locked_version(dir: &Path) -> Option<String> {
+    let file_contents = read_manifest(dir, VERSION_LOCK_FILE).ok()??;
+    extract_plain_version(&file_contents).ok()
+}
+
+/// Describe where the version shown for `package` comes from, for `starship explain`
+fn explain_package_version(
+    package: &PackageVersion,
+    resolved: &str,
+    shown: &str,
+    config: &PackageConfig,
+) -> String {
+    let source = match package.manifest.label() {
+        Some(label) => format!("{:?} manifest, {} ecosystem", package.manifest, label),
+        None => format!("{:?} manifest", package.manifest),
+    };
+    let mut explanation = format!(
+        "{}: {} read from {} ({})",
+        super::description("package"),
+        package.version,
+        package.file.display(),
+        source,
+    );
+
+    let mut transforms = Vec::new();
+    // Placeholder versions are replaced by the git tag before anything else
+    if resolved != package.version {
+        transforms.push(format!(
+            "resolve_git_version ({} from git describe)",
+            resolved
+        ));
+    }
+    if map_version(resolved, &config.version_map).is_some() {
+        transforms.push("version_map".to_string());
+    } else {
+        if let Some(prefix) = config.trim_prefix {
+            transforms.push(format!("trim_prefix = {:?}", prefix));
+        }
+        if config.hide_calver_prefix && is_calver(resolved) {
+            transforms.push("hide_calver_prefix".to_string());
+        }
+        if config.version_case != VersionCase::Preserve {
+            transforms.push(format!("version_case = {:?}", config.version_case));
+        }
+    }
+    if !transforms.is_empty() {
+        explanation += &format!(", shown as {} after {}", shown, transforms.join(", "));
+    }
+    explanation
+}
+
+/// Shorten a package name to `max_length` characters with an ellipsis. The scope of scoped
+/// npm names like `@scope/package` is shortened first, since the package part says more.
+fn truncate_name(name: &str, max_length: usize) -> String {
+    if name.chars().count() <= max_length {
+        return name.to_string();
+    }
+
+    if let Some((scope, package)) = name.strip_prefix('@').and_then(|name| name.split_once('/')) {
+        let package_length = package.chars().count();
+        // Room for the `@`, the ellipsis, the `/` and at least one character of the scope
+        if package_length + 4 <= max_length {
+            let scope: String = scope
+                .chars()
+                .take(max_length - package_length - 3)
+                .collect();
+            return format!("@{}…/{}", scope, package);
+        }
+        return truncate_end(package, max_length);
+    }
+    truncate_end(name, max_length)
+}
+
+/// Shorten `text` to `max_length` characters, ending it with an ellipsis
+fn truncate_end(text: &str, max_length: usize) -> String {
+    let kept: String = text.chars().take(max_length.saturating_sub(1)).collect();
+    format!("{}…", kept)
+}
+
+/// Surround `symbol` with `symbol_prefix` and `symbol_suffix`, keeping the space separating it
+/// from the version outside of them
+fn pad_symbol(symbol: &str, config: &PackageConfig) -> String {
+    let glyph = symbol.trim_end();
+    format!(
+        "{}{}{}{}",
+        config.symbol_prefix,
+        glyph,
+        config.symbol_suffix,
+        &symbol[glyph.len()..]
+    )
+}
+
+/// Percent-encode the whitespace and control characters of a package name for use in a URL.
+/// A BEL in a name read from a manifest would otherwise end an OSC 8 hyperlink early.
+fn encode_url_name(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_control() || c.is_whitespace() {
+            let mut buffer = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                encoded += &format!("%{:02X}", byte);
+            }
+        } else {
+            encoded.push(c);
+        }
+    }
+    encoded
+}
+
+/// Make `text` a link to `url` in terminals supporting OSC 8 hyperlinks
+fn format_hyperlink(url: &str, text: &str) -> String {
+    format!("\u{1b}]8;;{}\u{7}{}\u{1b}]8;;\u{7}", url, text)
+}
+
+/// Format a version captured by one of the `VERSION_PATTERNS`, rejecting captures that
+/// can't be a version, like a variable name in an oddly formatted manifest
+fn format_captured_version(raw_version: &str) -> Result<String, PackageError> {
+    let raw_version = raw_version.trim();
+    let is_plausible = raw_version.chars().any(|c| c.is_ascii_digit())
+        && raw_version.len() <= 64
+        && !raw_version.contains(char::is_whitespace);
+    if !is_plausible {
+        log::debug!("Ignoring implausible version {:?}", raw_version);
+        return Err(PackageError::NoVersionField);
+    }
+
+    Ok(format_version(raw_version))
+}
+
+/// Turn JSON with comments and trailing commas (JSONC) into strict JSON
+fn strip_jsonc(file_contents: &str) -> String {
+    let without_comments = strip_jsonc_comments(file_contents);
+    let mut stripped = String::with_capacity(without_comments.len());
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, c) in without_comments.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            // Drop commas directly followed by the end of an object or array
+            let next = without_comments[index + 1..].trim_start().chars().next();
+            if next == Some('}') || next == Some(']') {
+                continue;
+            }
+        }
+        stripped.push(c);
+    }
+
+    stripped
+}
+
+/// Remove `//` and `/* */` comments outside of JSON strings
+fn strip_jsonc_comments(file_contents: &str) -> String {
+    let mut stripped = String::with_capacity(file_contents.len());
+    let mut chars = file_contents.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            stripped.push(c);
+            match c {
+                '\\' => stripped.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            _ => {
+                in_string = c == '"';
+                stripped.push(c);
+            }
+        }
+    }
+
+    stripped
+}
+
+/// Whether `version` is one of the configured placeholder versions, e.g. the `0.0.0`
+/// left in manifests by dynamic versioning tools
+fn is_placeholder_version(version: &str, placeholder_versions: &[&str]) -> bool {
+    placeholder_versions
+        .iter()
+        .any(|placeholder| format_version(placeholder) == version)
+}
+
+/// The label `version_map` shows instead of a version, matched exactly against the version
+/// as it is written in the manifest or with the `v` that `format_version` adds
+fn map_version<'a>(version: &str, version_map: &HashMap<String, &'a str>) -> Option<&'a str> {
+    version_map
+        .get(version)
+        .or_else(|| version_map.get(version.strip_prefix('v')?))
+        .copied()
+}
+
+/// Strip `prefix` from a raw version, e.g. `release-` from `release-1.2.3`, and format it again
+fn trim_version_prefix(version: &str, prefix: &str) -> String {
+    // `format_version` may have added a `v` in front of the prefix
+    let trimmed = version
+        .strip_prefix(prefix)
+        .or_else(|| version.strip_prefix('v')?.strip_prefix(prefix));
+
+    match trimmed {
+        Some(trimmed) if !prefix.is_empty() => format_version(trimmed),
+        _ => version.to_string(),
+    }
+}
+
+/// Whether a version follows a date-based scheme starting with the year, like `2024.03`
+fn is_calver(version: &str) -> bool {
+    version_regex("calver").is_match(version)
+}
+
+/// Whether a version is still in initial development, i.e. has a major version of 0 like
+/// `v0.9.0`. Versions that don't start with a number are considered stable.
+fn is_unstable_version(version: &str) -> bool {
+    let version = version.strip_prefix('v').unwrap_or(version);
+    let major = version
+        .split(|c: char| !c.is_ascii_digit())
+        .next()
+        .unwrap_or_default();
+    !major.is_empty() && major.parse() == Ok(0)
+}
+
+/// Remove the `v` added by `format_version` from CalVer versions, which aren't semver
+fn trim_calver_prefix(version: &str) -> String {
+    match version.strip_prefix('v') {
+        Some(calver) if is_calver(version) => calver.to_string(),
+        _ => version.to_string(),
+    }
+}
+
+/// Change the case of a formatted version, leaving its `v` prefix as is
+fn apply_version_case(version: &str, case: VersionCase) -> String {
+    let (prefix, rest) = match version.strip_prefix('v') {
+        Some(rest) => ("v", rest),
+        None => ("", version),
+    };
+
+    match case {
+        VersionCase::Preserve => version.to_string(),
+        VersionCase::Lower => format!("{}{}", prefix, rest.to_lowercase()),
+        VersionCase::Upper => format!("{}{}", prefix, rest.to_uppercase()),
+    }
+}
+
+/// Strip escape sequences and other control characters from a version read from a file,
+/// so that a manifest can't change the styling of the prompt, or worse
+fn sanitize_version(version: &str) -> String {
+    version_regex("escape_sequence")
+        .replace_all(version, "")
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect()
+}
+
+fn format_version(version: &str) -> String {
+    let cleaned = sanitize_version(version)
+        .replace('"', "")
+        .trim()
+        .to_string();
+    if cleaned.starts_with('v') {
+        cleaned
+    } else {
+        format!("v{}", cleaned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StarshipConfig;
+    use crate::configs::package::CustomRegexManifest;
+    use crate::modules::utils::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::cell::Cell;
+    use std::fs;
+    use std::io;
+    use std::rc::Rc;
+
+    const CARGO_TOML: &str = "[package]\nname = \"starship\"\nversion = \"0.1.0\"\n";
+
+    fn fill_config(dir: &tempfile::TempDir, file_name: &str, contents: &str) -> io::Result<()> {
+        let path = dir.path().join(file_name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)
+    }
+
+    #[test]
+    fn test_format_version() {
+        assert_eq!(format_version("0.1.0"), "v0.1.0");
+        assert_eq!(format_version(" 0.1.0 "), "v0.1.0");
+        assert_eq!(format_version("0.1.0 "), "v0.1.0");
+        assert_eq!(format_version(" 0.1.0"), "v0.1.0");
+        assert_eq!(format_version("\"0.1.0\""), "v0.1.0");
+
+        assert_eq!(format_version("v0.1.0"), "v0.1.0");
+        assert_eq!(format_version(" v0.1.0 "), "v0.1.0");
+        assert_eq!(format_version(" v0.1.0"), "v0.1.0");
+        assert_eq!(format_version("v0.1.0 "), "v0.1.0");
+        assert_eq!(format_version("\"v0.1.0\""), "v0.1.0");
+    }
+
+    #[test]
+    fn test_sanitize_version() {
+        assert_eq!(format_version("\u{1b}[31m0.1.0\u{1b}[0m"), "v0.1.0");
+        assert_eq!(format_version("0.1.0\u{1b}[2J\u{1b}[H"), "v0.1.0");
+        assert_eq!(
+            format_version("\u{1b}]8;;https://example.com\u{7}0.1.0\u{1b}]8;;\u{7}"),
+            "v0.1.0"
+        );
+        assert_eq!(format_version("0.1\u{0}.0\r\n"), "v0.1.0");
+        assert_eq!(format_version("0.1.0-ß"), "v0.1.0-ß");
+    }
+
+    #[test]
+    fn folder_with_escape_sequence_in_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "package.json", r#"{"version": "0.1.0\u001b[8m"}"#)?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_version_patterns_compile() {
+        for (name, pattern) in VERSION_PATTERNS {
+            assert!(Regex::new(pattern).is_ok(), "Invalid pattern {}", name);
+        }
+        assert_eq!(VERSION_REGEXES.len(), VERSION_PATTERNS.len());
+    }
+
+    #[test]
+    fn test_format_captured_version() {
+        assert_eq!(format_captured_version("1.2.3"), Ok("v1.2.3".to_string()));
+        assert_eq!(
+            format_captured_version("2024.03-rc1+build"),
+            Ok("v2024.03-rc1+build".to_string())
+        );
+        for &invalid in &[
+            "",
+            "projectVersion",
+            "${version} of the project",
+            &"1".repeat(65),
+        ] {
+            assert_eq!(
+                format_captured_version(invalid),
+                Err(PackageError::NoVersionField),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_trim_version_prefix() {
+        assert_eq!(
+            trim_version_prefix(&format_version("release-1.2.3"), "release-"),
+            "v1.2.3"
+        );
+        assert_eq!(
+            trim_version_prefix(&format_version("app@1.2.3"), "app@"),
+            "v1.2.3"
+        );
+        assert_eq!(
+            trim_version_prefix(&format_version("version-1.2.3"), "version-"),
+            "v1.2.3"
+        );
+        assert_eq!(
+            trim_version_prefix(&format_version("1.2.3"), "release-"),
+            "v1.2.3"
+        );
+    }
+
+    #[test]
+    fn test_trim_calver_prefix() {
+        for &(version, expected) in &[
+            ("v2024.03", "2024.03"),
+            ("v2024.3.1", "2024.3.1"),
+            ("v1999-12-31", "1999-12-31"),
+            // Short years can't be told apart from semver majors
+            ("v22.1.0", "v22.1.0"),
+            ("v1.2.3", "v1.2.3"),
+            ("v2024", "v2024"),
+        ] {
+            assert_eq!(trim_calver_prefix(version), expected, "{}", version);
+        }
+    }
+
+    #[test]
+    fn folder_with_calver_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "package.json", r#"{"version": "2024.03"}"#)?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v2024.03")
+        ));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                hide_calver_prefix = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 2024.03")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_truncate_name() {
+        for &(name, max_length, expected) in &[
+            ("starship", 20, "starship"),
+            ("starship-prompt", 8, "starshi…"),
+            (
+                "@my-org/some-really-long-package",
+                40,
+                "@my-org/some-really-long-package",
+            ),
+            (
+                "@my-org/some-really-long-package",
+                30,
+                "@my-…/some-really-long-package",
+            ),
+            (
+                "@my-org/some-really-long-package",
+                20,
+                "some-really-long-pa…",
+            ),
+            ("@my-org/pkg", 8, "@my…/pkg"),
+            ("ünïcödé-nämé", 8, "ünïcödé…"),
+        ] {
+            let actual = truncate_name(name, max_length);
+            assert_eq!(actual, expected, "{} {}", name, max_length);
+            assert!(actual.chars().count() <= max_length);
+        }
+    }
+
+    #[test]
+    fn test_apply_version_case() {
+        let version = format_version("1.0.0-Rc1");
+        assert_eq!(
+            apply_version_case(&version, VersionCase::Preserve),
+            "v1.0.0-Rc1"
+        );
+        assert_eq!(
+            apply_version_case(&version, VersionCase::Lower),
+            "v1.0.0-rc1"
+        );
+        assert_eq!(
+            apply_version_case(&version, VersionCase::Upper),
+            "v1.0.0-RC1"
+        );
+    }
+
+    #[test]
+    fn test_extract_name() {
+        assert_eq!(
+            ManifestKind::Cargo.extract_name(CARGO_TOML),
+            Some("starship".to_string())
+        );
+        assert_eq!(
+            ManifestKind::Npm.extract_name("{ \"name\": \"@starship/prompt\" }"),
+            Some("@starship/prompt".to_string())
+        );
+        assert_eq!(
+            ManifestKind::PyProject.extract_name("[tool.poetry]\nname = \"starship\"\n"),
+            Some("starship".to_string())
+        );
+        assert_eq!(
+            ManifestKind::Composer.extract_name("{ \"name\": \"starship/prompt\" }"),
+            Some("starship/prompt".to_string())
+        );
+        assert_eq!(
+            ManifestKind::Npm.extract_name("{ \"private\": true }"),
+            None
+        );
+    }
+
+    #[test]
+    fn folder_with_escape_sequences_in_name() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "package.json",
+            r#"{"name": "evil\u0007\u001b[31mred", "version": "0.1.0"}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                display_name = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 evilred v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_registry_url() {
+        assert_eq!(
+            ManifestKind::Composer.registry_url("starship/prompt", None),
+            Some("https://packagist.org/packages/starship/prompt".to_string())
+        );
+        assert_eq!(
+            ManifestKind::Cargo.registry_url("starship", Some("https://crates.example.com/")),
+            Some("https://crates.example.com/crates/starship".to_string())
+        );
+        assert_eq!(ManifestKind::Gradle.registry_url("starship", None), None);
+        assert_eq!(
+            ManifestKind::Npm.registry_url("evil\u{7}\u{1b}[31mred name", None),
+            Some("https://www.npmjs.com/package/evil%07%1B[31mred%20name".to_string())
+        );
+        assert_eq!(
+            ManifestKind::Npm.latest_version_url("evil\u{85}", None),
+            Some("https://registry.npmjs.org/evil%C2%85/latest".to_string())
+        );
+    }
+
+    #[test]
+    fn folder_with_control_characters_in_linked_name() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "package.json",
+            "{ \"name\": \"evil\\u0007injected\", \"version\": \"0.1.0\" }",
+        )?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                link_to_registry = true
+            })
+            .collect()
+            .unwrap();
+        assert!(actual.contains("https://www.npmjs.com/package/evilinjected\u{7}"));
+        assert_eq!(actual.matches('\u{7}').count(), 2);
+        dir.close()
+    }
+
+    #[test]
+    fn test_latest_version_url() {
+        assert_eq!(
+            ManifestKind::Cargo.latest_version_url("starship", None),
+            Some("https://crates.io/api/v1/crates/starship".to_string())
+        );
+        assert_eq!(
+            ManifestKind::Npm.latest_version_url("starship", Some("https://npm.example.com")),
+            Some("https://npm.example.com/starship/latest".to_string())
+        );
+        assert_eq!(
+            ManifestKind::PyProject.latest_version_url("starship", None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_extract_cargo_version() {
+        let cargo_with_version = toml::toml! {
+            [package]
+            name = "starship"
+            version = "0.1.0"
+        }
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_cargo_version(&cargo_with_version).ok(),
+            expected_version
+        );
+
+        let cargo_without_version = toml::toml! {
+            [package]
+            name = "starship"
+        }
+        .to_string();
+
+        let expected_version = None;
+        assert_eq!(
+            extract_cargo_version(&cargo_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_cargo_workspace_version() {
+        let workspace_root = toml::toml! {
+            [workspace]
+            members = ["crates/*"]
+
+            [workspace.package]
+            version = "0.1.0"
+        }
+        .to_string();
+        assert_eq!(
+            extract_cargo_version(&workspace_root),
+            Ok("v0.1.0".to_string())
+        );
+
+        let workspace_without_version = toml::toml! {
+            [workspace]
+            members = ["crates/*"]
+        }
+        .to_string();
+        assert_eq!(
+            extract_cargo_version(&workspace_without_version),
+            Err(PackageError::NoVersionField)
+        );
+
+        // A root package takes precedence over the versions it shares with members
+        let root_package = toml::toml! {
+            [package]
+            name = "starship"
+            version = "0.2.0"
+
+            [workspace.package]
+            version = "0.1.0"
+        }
+        .to_string();
+        assert_eq!(
+            extract_cargo_version(&root_package),
+            Ok("v0.2.0".to_string())
+        );
+    }
+
+    #[test]
+    fn folder_with_cargo_workspace_root() -> io::Result<()> {
+        let project_dir = tempfile::tempdir()?;
+        fill_config(
+            &project_dir,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.package]\nversion = \"0.1.0\"\n",
+        )?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(project_dir.path())
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_is_unpublishable_crate() {
+        assert!(!is_unpublishable_crate(CARGO_TOML));
+        assert!(is_unpublishable_crate(&format!(
+            "{}publish = false\n",
+            CARGO_TOML
+        )));
+        assert!(is_unpublishable_crate(&format!(
+            "{}publish = []\n",
+            CARGO_TOML
+        )));
+        assert!(!is_unpublishable_crate(&format!(
+            "{}publish = [\"internal\"]\n",
+            CARGO_TOML
+        )));
+    }
+
+    #[test]
+    fn test_extract_package_version() {
+        let package_with_version = json::json!({
+            "name": "spacefish",
+            "version": "0.1.0"
+        })
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_package_version(&package_with_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_package_version_without_version() {
+        let package_without_version = json::json!({
+            "name": "spacefish"
+        })
+        .to_string();
+
+        let expected_version = None;
+        assert_eq!(
+            extract_package_version(&package_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_package_version_with_null_version() {
+        let package_with_null_version = json::json!({
+            "name": "spacefish",
+            "version": null
+        })
+        .to_string();
+
+        let expected_version = None;
+        assert_eq!(
+            extract_package_version(&package_with_null_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_package_version_with_null_string_version() {
+        let package_with_null_string_version = json::json!({
+            "name": "spacefish",
+            "version": "null"
+        })
+        .to_string();
+
+        let expected_version = None;
+        assert_eq!(
+            extract_package_version(&package_with_null_string_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_private_package_version() {
+        let private_package = json::json!({
+            "name": "spacefish",
+            "version": "0.1.0",
+            "private": true
+        })
+        .to_string();
+
+        let expected_version = None;
+        assert_eq!(
+            extract_package_version(&private_package).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_lerna_version() {
+        let fixed = json::json!({
+            "version": "0.1.0",
+            "packages": ["packages/*"]
+        })
+        .to_string();
+        assert_eq!(extract_lerna_version(&fixed), Ok("v0.1.0".to_string()));
+
+        let independent = json::json!({
+            "version": "independent",
+            "packages": ["packages/*"]
+        })
+        .to_string();
+        assert_eq!(
+            extract_lerna_version(&independent),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn folder_with_lerna_json() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "package.json",
+            &json::json!({ "name": "monorepo", "private": true }).to_string(),
+        )?;
+        fill_config(&dir, "lerna.json", r#"{ "version": "0.1.0" }"#)?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+
+        fill_config(&dir, "lerna.json", r#"{ "version": "independent" }"#)?;
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        assert_eq!(actual, None);
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_deno_version() {
+        let deno_json = json::json!({
+            "name": "@starship/starship",
+            "version": "0.1.0",
+            "exports": "./mod.ts"
+        })
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(extract_deno_version(&deno_json).ok(), expected_version);
+
+        let deno_jsonc = r#"{
+  // The package published to JSR
+  "name": "@starship/starship",
+  "version": "0.1.0", /* bumped by CI */
+  "tasks": {
+    "dev": "deno run --watch mod.ts",
+  },
+}"#;
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(extract_deno_version(deno_jsonc).ok(), expected_version);
+    }
+
+    #[test]
+    fn test_extract_deno_module_version() {
+        let mod_ts = "// Copyright the Starship authors
+import { format } from \"./format.ts\";
+
+export const VERSION = \"0.1.0\";
+export { format };
+";
+        let expected_version = Ok("v0.1.0".to_string());
+        assert_eq!(extract_deno_module_version(mod_ts), expected_version);
+
+        let single_quoted = "export const VERSION = '0.1.0';\n";
+        let expected_version = Ok("v0.1.0".to_string());
+        assert_eq!(extract_deno_module_version(single_quoted), expected_version);
+
+        let without_version = "export const NAME = \"starship\";\nexport function format() {}\n";
+        assert_eq!(
+            extract_deno_module_version(without_version),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn folder_with_deno_json_without_version_and_mod_ts() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "deno.json", "{ \"exports\": \"./mod.ts\" }")?;
+        fill_config(&dir, "mod.ts", "export const VERSION = \"0.1.0\";\n")?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+
+        fill_config(&dir, "mod.ts", "export function format() {}\n")?;
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        assert_eq!(actual, None);
+        dir.close()
+    }
+
+    #[test]
+    fn test_strip_jsonc() {
+        let jsonc = r#"{
+  // A comment
+  "url": "https://starship.rs", /* Another comment */
+  "list": [1, 2, 3,],
+  "escaped": "a \" // not a comment",
+}"#;
+
+        assert!(json::from_str::<json::Value>(jsonc).is_err());
+
+        let expected = json::json!({
+            "url": "https://starship.rs",
+            "list": [1, 2, 3],
+            "escaped": "a \" // not a comment"
+        });
+        assert_eq!(
+            json::from_str::<json::Value>(&strip_jsonc(jsonc)).ok(),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn test_extract_poetry_version() {
+        let poetry_with_version = toml::toml! {
+            [tool.poetry]
+            name = "starship"
+            version = "0.1.0"
+        }
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_poetry_version(&poetry_with_version).ok(),
+            expected_version
+        );
+
+        let poetry_without_version = toml::toml! {
+            [tool.poetry]
+            name = "starship"
+        }
+        .to_string();
+
+        let expected_version = None;
+        assert_eq!(
+            extract_poetry_version(&poetry_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_poetry_dependency_constraints_are_not_versions() -> io::Result<()> {
+        let poetry_without_version = toml::toml! {
+            [tool.poetry]
+            name = "starship"
+
+            [tool.poetry.dependencies]
+            python = "^3.9"
+            requests = "~2.31"
+            version = "^1.0"
+
+            [tool.poetry.group.dev.dependencies]
+            pytest = "^8.0"
+        }
+        .to_string();
+
+        assert_eq!(
+            extract_poetry_version(&poetry_without_version),
+            Err(PackageError::NoVersionField)
+        );
+
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "pyproject.toml", &poetry_without_version)?;
+        assert_eq!(
+            get_python_version(&poetry_without_version, dir.path(), &PackageConfig::new()),
+            Err(PackageError::NoVersionField)
+        );
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        assert_eq!(actual, None);
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_commitizen_version() -> io::Result<()> {
+        let commitizen_toml = toml::toml! {
+            [project]
+            name = "starship"
+            dynamic = ["version"]
+
+            [tool.commitizen]
+            name = "cz_conventional_commits"
+            version = "0.1.0"
+            tag_format = "v$version"
+        }
+        .to_string();
+
+        assert_eq!(
+            extract_commitizen_version(&commitizen_toml),
+            Ok("v0.1.0".to_string())
+        );
+
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "pyproject.toml", &commitizen_toml)?;
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+
+        // A static PEP 621 version takes precedence over the commitizen one
+        let pep621_toml = commitizen_toml.replace("dynamic = [\"version\"]", "version = \"0.2.0\"");
+        assert_eq!(
+            get_python_version(&pep621_toml, dir.path(), &PackageConfig::new()),
+            Ok("v0.2.0".to_string())
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_flit_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join("starship"))?;
+        fill_config(
+            &dir,
+            "starship/__init__.py",
+            "\"\"\"The cross-shell prompt\"\"\"\n\n__version__ = '0.1.0'\n",
+        )?;
+
+        let flit_with_module = toml::toml! {
+            [tool.flit.metadata]
+            module = "starship"
+            author = "Matan Kushner"
+        }
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_flit_version(&flit_with_module, dir.path()).ok(),
+            expected_version
+        );
+
+        let flit_with_missing_module = toml::toml! {
+            [tool.flit.metadata]
+            module = "spacefish"
+        }
+        .to_string();
+
+        let expected_version = None;
+        assert_eq!(
+            extract_flit_version(&flit_with_missing_module, dir.path()).ok(),
+            expected_version
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_pep621_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let pep621 = toml::toml! {
+            [project]
+            name = "starship"
+            version = "0.1.0"
+        }
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_pep621_version(&pep621, dir.path(), &PackageConfig::new()).ok(),
+            expected_version
+        );
+        dir.close()
+    }
+
+    /// A `pyproject.toml` with a version computed by setuptools-scm, in a repository tagged `v0.1.0`
+    fn setuptools_scm_repo() -> Result<tempfile::TempDir, Box<dyn std::error::Error>> {
+        let pyproject = toml::toml! {
+            [project]
+            name = "starship"
+            dynamic = ["version"]
+
+            [tool.setuptools_scm]
+        }
+        .to_string();
+        tagged_repo("pyproject.toml", &pyproject)
+    }
+
+    /// A git repository with a single commit of `file_name`, tagged `v0.1.0`
+    fn tagged_repo(
+        file_name: &str,
+        contents: &str,
+    ) -> Result<tempfile::TempDir, Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, file_name, contents)?;
+
+        let repository = git2::Repository::init(dir.path())?;
+        let mut index = repository.index()?;
+        index.add_path(Path::new(file_name))?;
+        index.write()?;
+        let tree = repository.find_tree(index.write_tree()?)?;
+        let signature = git2::Signature::now("Starship", "starship@example.com")?;
+        let commit = repository.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Initial commit",
+            &tree,
+            &[],
+        )?;
+        repository.tag_lightweight("v0.1.0", &repository.find_object(commit, None)?, false)?;
+        Ok(dir)
+    }
+
+    #[test]
+    fn folder_with_setuptools_scm_in_tagged_repo() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = setuptools_scm_repo()?;
+        let config = toml::toml! {
+            [package]
+            resolve_git_version = true
+        };
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config)
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn folder_with_setuptools_scm_without_git_version() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = setuptools_scm_repo()?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn folder_with_setuptools_scm_in_clean_and_dirty_repo() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let dir = setuptools_scm_repo()?;
+        let config = toml::toml! {
+            [package]
+            resolve_git_version = true
+            require_clean_repo = true
+        };
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+
+        let pyproject = fs::read_to_string(dir.path().join("pyproject.toml"))?;
+        fill_config(
+            &dir,
+            "pyproject.toml",
+            &format!("{}\n# changed\n", pyproject),
+        )?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config)
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0+dirty")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_warm_package_cache() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = setuptools_scm_repo()?;
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), dir.path());
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [package]
+                resolve_git_version = true
+            }),
+        };
+        assert_eq!(warm_package_cache(&context), Some("v0.1.0".to_string()));
+
+        let config = PackageConfig {
+            resolve_git_version: true,
+            ..PackageConfig::new()
+        };
+
+        // Retagging doesn't touch `pyproject.toml` or the current commit, but still replaces
+        // the version cached on disk
+        let repository = git2::Repository::open(dir.path())?;
+        let head = repository.head()?.peel(git2::ObjectType::Commit)?;
+        repository.tag_delete("v0.1.0")?;
+        repository.tag_lightweight("v0.2.0", &head, false)?;
+        assert_eq!(
+            get_package_version(dir.path(), &config).map(|package| package.version),
+            Ok("v0.2.0".to_string())
+        );
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_warm_package_cache_with_placeholder_version() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let dir = tagged_repo(
+            "Cargo.toml",
+            "[package]\nname = \"starship\"\nversion = \"0.0.0\"\n",
+        )?;
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), dir.path());
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [package]
+                resolve_git_version = true
+            }),
+        };
+        assert_eq!(warm_package_cache(&context), Some("v0.1.0".to_string()));
+
+        let cached = cached_version(
+            &dir.path().join("Cargo.toml"),
+            &PackageConfig::new(),
+            || panic!("git queried despite a warmed cache"),
+        );
+        assert_eq!(cached, Some("v0.1.0".to_string()));
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_describe() {
+        for &(describe, describe_format, expected) in &[
+            (
+                "v1.2.3-5-gabcdef0",
+                DescribeFormat::Full,
+                "v1.2.3-5-gabcdef0",
+            ),
+            ("v1.2.3-5-gabcdef0", DescribeFormat::Tag, "v1.2.3"),
+            ("v1.2.3-5-gabcdef0", DescribeFormat::TagDistance, "v1.2.3+5"),
+            ("v1.2.3-rc-1-5-gabcdef0", DescribeFormat::Tag, "v1.2.3-rc-1"),
+            (
+                "v1.2.3-5-gabcdef0+dirty",
+                DescribeFormat::Tag,
+                "v1.2.3+dirty",
+            ),
+            (
+                "v1.2.3-5-gabcdef0+dirty",
+                DescribeFormat::TagDistance,
+                "v1.2.3+5+dirty",
+            ),
+            ("v1.2.3", DescribeFormat::TagDistance, "v1.2.3"),
+            ("v1.2.3+dirty", DescribeFormat::Tag, "v1.2.3+dirty"),
+        ] {
+            assert_eq!(
+                format_describe(describe, describe_format),
+                expected,
+                "{} {:?}",
+                describe,
+                describe_format
+            );
+        }
+    }
+
+    #[test]
+    fn test_cached_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache_dir = dir.path().join("cache");
+        let manifest = dir.path().join("pyproject.toml");
+        fs::write(&manifest, "[project]\n")?;
+        let config = PackageConfig::new();
+        let mut resolved = 0;
+
+        let version = cached_version_in(&cache_dir, &manifest, &config, || {
+            resolved += 1;
+            Some("v0.1.0".to_string())
+        });
+        assert_eq!(version, Some("v0.1.0".to_string()));
+
+        // The cache is read back from disk, like it is by the next prompt
+        let version = cached_version_in(&cache_dir, &manifest, &config, || {
+            resolved += 1;
+            Some("v0.2.0".to_string())
+        });
+        assert_eq!(version, Some("v0.1.0".to_string()));
+        assert_eq!(resolved, 1);
+
+        let modified = fs::metadata(&manifest)?.modified()?;
+        fs::File::options()
+            .write(true)
+            .open(&manifest)?
+            .set_modified(modified + std::time::Duration::from_secs(1))?;
+
+        let version = cached_version_in(&cache_dir, &manifest, &config, || {
+            resolved += 1;
+            None
+        });
+        assert_eq!(version, None);
+        assert_eq!(resolved, 2);
+
+        // Not finding a version is cached too
+        let version = cached_version_in(&cache_dir, &manifest, &config, || {
+            resolved += 1;
+            Some("v0.2.0".to_string())
+        });
+        assert_eq!(version, None);
+        assert_eq!(resolved, 2);
+        dir.close()
+    }
+
+    #[test]
+    fn test_cached_version_after_commit() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = setuptools_scm_repo()?;
+        let cache_dir = tempfile::tempdir()?;
+        let manifest = dir.path().join("pyproject.toml");
+        let config = PackageConfig::new();
+
+        let version = cached_version_in(cache_dir.path(), &manifest, &config, || {
+            Some("v0.1.0".into())
+        });
+        assert_eq!(version, Some("v0.1.0".to_string()));
+
+        let repository = git2::Repository::open(dir.path())?;
+        let head = repository.head()?.peel_to_commit()?;
+        let signature = git2::Signature::now("Starship", "starship@example.com")?;
+        repository.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Second commit",
+            &head.tree()?,
+            &[&head],
+        )?;
+
+        let version = cached_version_in(cache_dir.path(), &manifest, &config, || {
+            Some("v0.1.0-1-gabcdef0".into())
+        });
+        assert_eq!(version, Some("v0.1.0-1-gabcdef0".to_string()));
+        cache_dir.close()?;
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_cached_version_with_other_options() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = setuptools_scm_repo()?;
+        let cache_dir = tempfile::tempdir()?;
+        let manifest = dir.path().join("pyproject.toml");
+        let config = PackageConfig {
+            require_clean_repo: true,
+            ..PackageConfig::new()
+        };
+
+        let version = cached_version_in(cache_dir.path(), &manifest, &config, || {
+            Some("v0.1.0".into())
+        });
+        assert_eq!(version, Some("v0.1.0".to_string()));
+
+        // Other options are resolved separately
+        let tag_config = PackageConfig {
+            describe_format: DescribeFormat::Tag,
+            ..config.clone()
+        };
+        let version = cached_version_in(cache_dir.path(), &manifest, &tag_config, || {
+            Some("v0.1.0-tag".into())
+        });
+        assert_eq!(version, Some("v0.1.0-tag".to_string()));
+
+        // So are uncommitted changes
+        fs::write(dir.path().join("README.md"), "# starship\n")?;
+        let repository = git2::Repository::open(dir.path())?;
+        let mut index = repository.index()?;
+        index.add_path(Path::new("README.md"))?;
+        index.write()?;
+        let version = cached_version_in(cache_dir.path(), &manifest, &config, || {
+            Some("v0.1.0+dirty".into())
+        });
+        assert_eq!(version, Some("v0.1.0+dirty".to_string()));
+        cache_dir.close()?;
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn folder_in_python_src_layout() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let pyproject = toml::toml! {
+            [project]
+            name = "mypkg"
+            version = "0.1.0"
+        }
+        .to_string();
+        fill_config(&dir, "pyproject.toml", &pyproject)?;
+        let sub_dir = dir.path().join("src").join("mypkg").join("subdir");
+        fs::create_dir_all(&sub_dir)?;
+        fs::write(sub_dir.join("__init__.py"), "")?;
+
+        let actual = ModuleRenderer::new("package").path(&sub_dir).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_in_python_src_layout_with_setuptools_scm() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = setuptools_scm_repo()?;
+        let sub_dir = dir.path().join("src").join("mypkg").join("subdir");
+        fs::create_dir_all(&sub_dir)?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(&sub_dir)
+            .config(toml::toml! {
+                [package]
+                resolve_git_version = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_python_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "starship.py", "__version__ = \"0.2.0\"\n")?;
+
+        let flit_only = toml::toml! {
+            [tool.flit.metadata]
+            module = "starship"
+        }
+        .to_string();
+
+        let expected_version = Some("v0.2.0".to_string());
+        assert_eq!(
+            get_python_version(&flit_only, dir.path(), &PackageConfig::new()).ok(),
+            expected_version
+        );
+
+        let poetry_and_flit = toml::toml! {
+            [tool.poetry]
+            version = "0.1.0"
+
+            [tool.flit.metadata]
+            module = "starship"
+        }
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            get_python_version(&poetry_and_flit, dir.path(), &PackageConfig::new()).ok(),
+            expected_version
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_gradle_version() {
+        let gradle_single_quotes = "plugins {
+    id 'java'
+    id 'test.plugin' version '0.2.0'
+}
+version '0.1.0'
+java {
+    sourceCompatibility = JavaVersion.VERSION_1_8
+    targetCompatibility = JavaVersion.VERSION_1_8
+}";
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_gradle_version(&gradle_single_quotes).ok(),
+            expected_version
+        );
+
+        let gradle_double_quotes = "plugins {
+    id 'java'
+    id 'test.plugin' version '0.2.0'
+}
+version \"0.1.0\"
+java {
+    sourceCompatibility = JavaVersion.VERSION_1_8
+    targetCompatibility = JavaVersion.VERSION_1_8
+}";
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_gradle_version(&gradle_double_quotes).ok(),
+            expected_version
+        );
+
+        let gradle_release_candidate = "plugins {
+    id 'java'
+    id 'test.plugin' version '0.2.0'
+}
+version '0.1.0-rc1'
+java {
+    sourceCompatibility = JavaVersion.VERSION_1_8
+    targetCompatibility = JavaVersion.VERSION_1_8
+}";
+
+        let expected_version = Some("v0.1.0-rc1".to_string());
+        assert_eq!(
+            extract_gradle_version(&gradle_release_candidate).ok(),
+            expected_version
+        );
+
+        let gradle_without_version = "plugins {
+    id 'java'
+    id 'test.plugin' version '0.2.0'
+}
+java {
+    sourceCompatibility = JavaVersion.VERSION_1_8
+    targetCompatibility = JavaVersion.VERSION_1_8
+}";
+
+        let expected_version = None;
+        assert_eq!(
+            extract_gradle_version(&gradle_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_resolve_property_references() {
+        let properties = "<revision>0.1.0</revision><changelist>-SNAPSHOT</changelist><sha1/>";
+        let lookup = |name: &str| find_xml_property(properties, name);
+        assert_eq!(
+            resolve_property_references(
+                "${revision}${sha1}${changelist}",
+                "maven_property_ref",
+                lookup
+            ),
+            Ok("0.1.0-SNAPSHOT".to_string())
+        );
+        assert_eq!(
+            resolve_property_references("${undefined}", "maven_property_ref", lookup),
+            Err(PackageError::NoVersionField)
+        );
+
+        // A property referencing itself would never be resolved
+        let lookup = |_: &str| Some("$(Version)".to_string());
+        assert_eq!(
+            resolve_property_references("$(Version)", "msbuild_property_ref", lookup),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn test_extract_maven_version() {
+        let pom = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <modelVersion>4.0.0</modelVersion>
+  <parent>
+    <groupId>rs.starship</groupId>
+    <artifactId>starship-parent</artifactId>
+    <version>1.0.0</version>
+  </parent>
+  <artifactId>starship</artifactId>
+  <version>0.1.0</version>
+  <dependencies>
+    <dependency>
+      <groupId>junit</groupId>
+      <artifactId>junit</artifactId>
+      <version>4.13.2</version>
+    </dependency>
+  </dependencies>
+</project>
+"#;
+        assert_eq!(extract_maven_version(pom), Ok("v0.1.0".to_string()));
+
+        let inherited = pom.replace("  <version>0.1.0</version>\n", "");
+        assert_eq!(extract_maven_version(&inherited), Ok("v1.0.0".to_string()));
+    }
+
+    #[test]
+    fn test_extract_maven_ci_friendly_version() {
+        let pom = |version: &str| {
+            format!(
+                r#"<project>
+  <modelVersion>4.0.0</modelVersion>
+  <groupId>rs.starship</groupId>
+  <artifactId>starship</artifactId>
+  <version>{}</version>
+  <properties>
+    <java.version>17</java.version>
+    <revision>0.1.0</revision>
+    <sha1/>
+    <changelist>-SNAPSHOT</changelist>
+  </properties>
+</project>
+"#,
+                version
+            )
+        };
+
+        assert_eq!(
+            extract_maven_version(&pom("${revision}")),
+            Ok("v0.1.0".to_string())
+        );
+        assert_eq!(
+            extract_maven_version(&pom("${revision}${sha1}${changelist}")),
+            Ok("v0.1.0-SNAPSHOT".to_string())
+        );
+        // e.g. passed with `-Dbuild.number=42`
+        assert_eq!(
+            extract_maven_version(&pom("${revision}.${build.number}")),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn test_extract_gradle_properties_version() {
+        for &properties in &[
+            "org.gradle.jvmargs=-Xmx2048m\nversion=0.1.0\n",
+            "kotlin.code.style=official\nVERSION_NAME = 0.1.0\nVERSION_CODE=1\n",
+        ] {
+            assert_eq!(
+                extract_gradle_properties_version(properties),
+                Ok("v0.1.0".to_string()),
+                "{}",
+                properties
+            );
+        }
+        assert_eq!(
+            extract_gradle_properties_version("kotlin.code.style=official\n"),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn folder_with_build_gradle_kts_and_gradle_properties() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "build.gradle.kts",
+            "plugins {\n    kotlin(\"multiplatform\") version \"1.9.0\"\n}\n",
+        )?;
+        fill_config(&dir, "gradle.properties", "VERSION_NAME=0.1.0\n")?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+
+        // A literal version in the build script wins
+        fill_config(
+            &dir,
+            "build.gradle.kts",
+            "group = \"rs.starship\"\nversion = \"0.2.0\"\n",
+        )?;
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.2.0")
+        ));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_version_with_implausible_capture() {
+        assert_eq!(
+            extract_gradle_version("version 'to be determined'"),
+            Err(PackageError::NoVersionField)
+        );
+        assert_eq!(
+            extract_mix_version("def project do\n  [app: :starship, version: \"dev\"]\nend"),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn test_extract_gleam_version() {
+        let gleam_toml = toml::toml! {
+            name = "starship"
+            version = "0.1.0"
+            target = "erlang"
+
+            [dependencies]
+            gleam_stdlib = { version = "0.2.0" }
+            gleeunit = "~> 1.0"
+
+            [dev-dependencies]
+            version = "0.3.0"
+        }
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(extract_project_version(&gleam_toml).ok(), expected_version);
+
+        let gleam_toml_without_version = toml::toml! {
+            name = "starship"
+
+            [dependencies]
+            gleam_stdlib = { version = "0.2.0" }
+        }
+        .to_string();
+
+        let expected_version = None;
+        assert_eq!(
+            extract_project_version(&gleam_toml_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_mix_version() {
+        let mix_complete = "defmodule MyApp.MixProject do
+  use Mix.Project
+
+  def project do
+    [
+      app: :my_app,
+      version: \"1.2.3\",
+      elixir: \"~> 1.10\",
+      start_permanent: Mix.env() == :prod,
+      deps: deps()
+    ]
+  end
+
+  # Run \"mix help compile.app\" to learn about applications.
+  def application do
+    [extra_applications: [:logger]]
+  end
+
+  # Run \"mix help deps\" to learn about dependencies.
+  defp deps do
+    []
+  end
+end";
+
+        let expected_version = Some("v1.2.3".to_string());
+        assert_eq!(extract_mix_version(&mix_complete).ok(), expected_version);
+
+        let mix_partial_oneline = "  def project, do: [app: :my_app,version: \"3.2.1\"]";
+
+        let expected_version = Some("v3.2.1".to_string());
+        assert_eq!(
+            extract_mix_version(&mix_partial_oneline).ok(),
+            expected_version
+        );
+
+        let mix_partial_prerelease = "  def project do
+    [
+      app: :my_app,
+      version: \"1.0.0-alpha.3\"
+    ]
+  end";
+
+        let expected_version = Some("v1.0.0-alpha.3".to_string());
+        assert_eq!(
+            extract_mix_version(&mix_partial_prerelease).ok(),
+            expected_version
+        );
+
+        let mix_partial_prerelease_and_build_info = "  def project do
+    [
+      app: :my_app,
+      version: \"0.9.9-dev+20130417140000.amd64\"
+    ]
+  end";
+
+        let expected_version = Some("v0.9.9-dev+20130417140000.amd64".to_string());
+        assert_eq!(
+            extract_mix_version(&mix_partial_prerelease_and_build_info).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_ant_version() {
+        let build_xml = "<?xml version=\"1.0\"?>
+<project name=\"starship\" default=\"dist\" basedir=\".\">
+  <property name=\"src\" location=\"src\"/>
+  <property name=\"build.version\" value=\"0.2.0\"/>
+  <property value='0.1.0' name='version' />
+  <property name=\"dist\" location=\"dist\"/>
+  <target name=\"dist\" depends=\"compile\"/>
+</project>";
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(extract_ant_version(build_xml).ok(), expected_version);
+
+        let build_xml_without_version = "<project name=\"starship\">
+  <property name=\"src\" location=\"src\"/>
+</project>";
+
+        let expected_version = None;
+        assert_eq!(
+            extract_ant_version(build_xml_without_version).ok(),
+            expected_version
+        );
+
+        // Ant expands properties at build time, their references aren't versions
+        let build_xml_with_reference = "<project name=\"starship\">
+  <property name=\"version\" value=\"${git.describe}\"/>
+</project>";
+
+        assert_eq!(
+            extract_ant_version(build_xml_with_reference),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn test_extract_composer_version() {
+        let composer_with_version = json::json!({
+            "name": "spacefish",
+            "version": "0.1.0"
+        })
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_composer_version(&composer_with_version).ok(),
+            expected_version
+        );
+
+        let composer_without_version = json::json!({
+            "name": "spacefish"
+        })
+        .to_string();
+
+        let expected_version = None;
+        assert_eq!(
+            extract_composer_version(&composer_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_project_version() {
+        let project_with_version = toml::toml! {
+            name = "starship"
+            version = "0.1.0"
+        }
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_project_version(&project_with_version).ok(),
+            expected_version
+        );
+
+        let project_without_version = toml::toml! {
+            [package]
+            name = "starship"
+        }
+        .to_string();
+
+        let expected_version = None;
+        assert_eq!(
+            extract_project_version(&project_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_mix_version_from_attribute() {
+        let mix_with_attribute = "defmodule MyApp.MixProject do
+  use Mix.Project
+
+  @source_url \"https://github.com/starship/starship\"
+  @version \"0.1.0\"
+
+  def project do
+    [app: :my_app, version: @version, source_url: @source_url]
+  end
+end";
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_mix_version(mix_with_attribute).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_vcpkg_version() {
+        let vcpkg_with_version = json::json!({
+            "name": "starship",
+            "version": "0.1.0"
+        })
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_vcpkg_version(&vcpkg_with_version).ok(),
+            expected_version
+        );
+
+        let vcpkg_with_version_semver = json::json!({
+            "name": "starship",
+            "version-semver": "1.2.3-rc.1"
+        })
+        .to_string();
+
+        let expected_version = Some("v1.2.3-rc.1".to_string());
+        assert_eq!(
+            extract_vcpkg_version(&vcpkg_with_version_semver).ok(),
+            expected_version
+        );
+
+        let vcpkg_with_version_date = json::json!({
+            "name": "starship",
+            "version-date": "2020-04-01"
+        })
+        .to_string();
+
+        let expected_version = Some("v2020-04-01".to_string());
+        assert_eq!(
+            extract_vcpkg_version(&vcpkg_with_version_date).ok(),
+            expected_version
+        );
+
+        let vcpkg_with_version_string = json::json!({
+            "name": "starship",
+            "version-string": "1.2.3"
+        })
+        .to_string();
+
+        let expected_version = Some("v1.2.3".to_string());
+        assert_eq!(
+            extract_vcpkg_version(&vcpkg_with_version_string).ok(),
+            expected_version
+        );
+
+        let vcpkg_with_multiple_versions = json::json!({
+            "name": "starship",
+            "version-string": "1.2.3",
+            "version": "0.1.0"
+        })
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_vcpkg_version(&vcpkg_with_multiple_versions).ok(),
+            expected_version
+        );
+
+        let vcpkg_without_version = json::json!({
+            "name": "starship"
+        })
+        .to_string();
+
+        let expected_version = None;
+        assert_eq!(
+            extract_vcpkg_version(&vcpkg_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_racket_version() {
+        let info_rkt = "#lang info
+(define collection \"starship\")
+(define deps '(\"base\" (\"rackunit-lib\" #:version \"1.5\")))
+(define build-deps '(\"scribble-lib\"))
+(define version \"1.2.3\")
+(define pkg-authors '(matchai))";
+
+        let expected_version = Some("v1.2.3".to_string());
+        assert_eq!(extract_racket_version(info_rkt).ok(), expected_version);
+
+        let info_rkt_without_version = "#lang info
+(define collection \"starship\")
+(define deps '(\"base\" (\"rackunit-lib\" #:version \"1.5\")))";
+
+        let expected_version = None;
+        assert_eq!(
+            extract_racket_version(info_rkt_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_webext_version() {
+        let webext_manifest = json::json!({
+            "manifest_version": 2,
+            "name": "starship",
+            "version": "0.1.0",
+            "permissions": ["tabs"]
+        })
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_webext_version(&webext_manifest).ok(),
+            expected_version
+        );
+
+        let generic_manifest = json::json!({
+            "name": "starship",
+            "version": "0.1.0"
+        })
+        .to_string();
+
+        let expected_version = Err(PackageError::NotFound);
+        assert_eq!(extract_webext_version(&generic_manifest), expected_version);
+    }
+
+    #[test]
+    fn test_extract_plugin_json_version() {
+        let grafana_plugin = json::json!({
+            "type": "panel",
+            "name": "Starship",
+            "id": "starship-panel",
+            "info": {
+                "author": { "name": "Starship" },
+                "version": "0.1.0",
+                "updated": "2020-01-01"
+            },
+            "dependencies": { "grafanaDependency": ">=7.0.0" }
+        })
+        .to_string();
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_plugin_json_version(&grafana_plugin).ok(),
+            expected_version
+        );
+
+        let generic_plugin = json::json!({
+            "name": "starship",
+            "info": { "version": "0.1.0" }
+        })
+        .to_string();
+
+        let expected_version = Err(PackageError::NotFound);
+        assert_eq!(
+            extract_plugin_json_version(&generic_plugin),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_zig_build_version() {
+        let build_zig = "const std = @import(\"std\");
+const version = \"1.2.3\";
+
+pub fn build(b: *std.Build) void {
+    const target = b.standardTargetOptions(.{});
+    _ = target;
+}";
+
+        let expected_version = Some("v1.2.3".to_string());
+        assert_eq!(extract_zig_build_version(build_zig).ok(), expected_version);
+
+        let build_zig_without_version = "const std = @import(\"std\");
+
+pub fn build(b: *std.Build) void {
+    const exe = b.addExecutable(.{ .name = \"starship\", .version = zig_version });
+    _ = exe;
+}";
+
+        let expected_version = None;
+        assert_eq!(
+            extract_zig_build_version(build_zig_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_podspec_version() {
+        let podspec_with_literal = "Pod::Spec.new do |s|
+  s.name         = 'Starship'
+  s.version      = '0.1.0'
+  s.swift_version = '5.0'
+  s.source       = { :git => 'https://github.com/starship/starship.git', :tag => s.version.to_s }
+end";
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_podspec_version(podspec_with_literal).ok(),
+            expected_version
+        );
+
+        let podspec_with_constant = "require_relative 'lib/starship/version'
+
+Pod::Spec.new do |spec|
+  spec.name    = \"Starship\"
+  spec.version = Starship::VERSION
+end";
+
+        let expected_version = None;
+        assert_eq!(
+            extract_podspec_version(podspec_with_constant).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_gemspec_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let gemspec_with_literal = "Gem::Specification.new do |spec|
+  spec.name    = \"starship\"
+  spec.version = \"0.1.0\"
+  spec.add_dependency \"rake\", \"~> 13.0\"
+end";
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_gemspec_version(gemspec_with_literal, dir.path(), false).ok(),
+            expected_version
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_gemspec_version_from_gem_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        for &version in &[
+            "Gem::Version.new(\"0.1.0\")",
+            "Gem::Version.new('0.1.0')",
+            "Gem::Version.new \"0.1.0\"",
+        ] {
+            let gemspec = format!(
+                "Gem::Specification.new do |spec|\n  spec.name    = \"starship\"\n  spec.version = {}\nend",
+                version
+            );
+            assert_eq!(
+                extract_gemspec_version(&gemspec, dir.path(), false),
+                Ok("v0.1.0".to_string()),
+                "{}",
+                version
+            );
+        }
+
+        let gemspec_with_magic_comment = "# frozen_string_literal: true
+
+Gem::Specification.new do |spec|
+  spec.name    = \"starship\"
+  spec.version = Gem::Version.new(\"0.1.0\")
+  spec.required_ruby_version = Gem::Requirement.new(\">= 3.0\")
+end";
+        assert_eq!(
+            extract_gemspec_version(gemspec_with_magic_comment, dir.path(), false),
+            Ok("v0.1.0".to_string())
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_gemspec_version_from_constant() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir_all(dir.path().join("lib/space_ship"))?;
+        fill_config(
+            &dir,
+            "lib/space_ship/version.rb",
+            "module SpaceShip\n  VERSION = \"0.1.0\"\nend\n",
+        )?;
+
+        let gemspec_with_constant = "require_relative \"lib/space_ship/version\"
+
+Gem::Specification.new do |spec|
+  spec.name    = \"space_ship\"
+  spec.version = SpaceShip::VERSION
+end";
+
+        let expected_version = None;
+        assert_eq!(
+            extract_gemspec_version(gemspec_with_constant, dir.path(), false).ok(),
+            expected_version
+        );
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_gemspec_version(gemspec_with_constant, dir.path(), true).ok(),
+            expected_version
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_gemspec_version_from_nested_constant() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir_all(dir.path().join("lib/space/ship"))?;
+        fill_config(
+            &dir,
+            "lib/space/ship/version.rb",
+            "module Space\n  module Ship\n    VERSION = '0.2.0'\n  end\nend\n",
+        )?;
+
+        let gemspec_with_constant = "Gem::Specification.new do |spec|
+  spec.name    = 'space-ship'
+  spec.version = Space::Ship::VERSION
+end";
+
+        let expected_version = Some("v0.2.0".to_string());
+        assert_eq!(
+            extract_gemspec_version(gemspec_with_constant, dir.path(), true).ok(),
+            expected_version
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_gemfile_lock_version() {
+        let lockfile = "\
+PATH
+  remote: .
+  specs:
+    starship (0.1.0)
+      rake (~> 13.0)
+
+GEM
+  remote: https://rubygems.org/
+  specs:
+    rake (13.0.6)
+    starship-plugin (0.2.0)
+
+PLATFORMS
+  ruby
+
+BUNDLED WITH
+   2.4.10
+";
+        assert_eq!(
+            extract_gemfile_lock_version(lockfile, "starship"),
+            Some("v0.1.0".to_string())
+        );
+        assert_eq!(
+            extract_gemfile_lock_version(lockfile, "rake"),
+            Some("v13.0.6".to_string())
+        );
+        assert_eq!(extract_gemfile_lock_version(lockfile, "starship-"), None);
+        assert_eq!(extract_gemfile_lock_version(lockfile, "rails"), None);
+    }
+
+    #[test]
+    fn folder_with_gemspec_and_gemfile_lock() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "starship.gemspec",
+            "Gem::Specification.new do |spec|\n  spec.name = \"starship\"\n  spec.version = Starship.version\nend\n",
+        )?;
+        fill_config(
+            &dir,
+            "Gemfile.lock",
+            "PATH\n  remote: .\n  specs:\n    starship (0.1.0)\n",
+        )?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        assert_eq!(None, actual);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                resolve_gemfile_lock = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_chef_version() {
+        let metadata_rb = "name 'starship'
+maintainer 'Starship'
+license 'ISC'
+description 'Installs the starship prompt'
+chef_version '>= 15.0'
+depends 'ark', '~> 5.0'
+depends 'git', '>= 10.0.0'
+version '0.1.0'
+supports 'ubuntu'
+";
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(extract_chef_version(metadata_rb).ok(), expected_version);
+
+        let metadata_rb_without_version = "name 'starship'
+depends 'ark', '~> 5.0'
+";
+
+        let expected_version = None;
+        assert_eq!(
+            extract_chef_version(metadata_rb_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_brew_formula_version() {
+        let formula = "class Starship < Formula
+  desc \"Cross-shell prompt for astronauts\"
+  homepage \"https://starship.rs\"
+  url \"https://github.com/starship/starship/archive/v0.2.0.tar.gz\"
+  version \"0.1.0\"
+  license \"ISC\"
+
+  depends_on \"rust\" => :build
+end";
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(extract_brew_formula_version(formula).ok(), expected_version);
+
+        let formula_with_url_version = "class Starship < Formula
+  url \"https://github.com/starship/starship/releases/download/starship-0.2.0.tar.gz\"
+  sha256 \"0000000000000000000000000000000000000000000000000000000000000000\"
+end";
+
+        let expected_version = Some("v0.2.0".to_string());
+        assert_eq!(
+            extract_brew_formula_version(formula_with_url_version).ok(),
+            expected_version
+        );
+
+        let ruby_script = "class Starship < Base\n  VERSION = \"0.1.0\"\nend";
+
+        let expected_version = Err(PackageError::NotFound);
+        assert_eq!(extract_brew_formula_version(ruby_script), expected_version);
+    }
+
+    #[test]
+    fn test_extract_dune_version() {
+        let dune_project = "(lang dune 3.0)
+(name starship)
+(version 0.1.0)
+
+(package
+ (name starship)
+ (depends
+  (ocaml (>= 4.08))
+  (dune (>= 3.0))))";
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(extract_dune_version(dune_project).ok(), expected_version);
+
+        let dune_project_without_version = "(lang dune 3.0)
+(name starship)";
+
+        let expected_version = None;
+        assert_eq!(
+            extract_dune_version(dune_project_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_opam_version() {
+        let opam = "opam-version: \"2.0\"
+name: \"starship\"
+version: \"0.1.0\"
+synopsis: \"The cross-shell prompt\"
+maintainer: [\"Matan Kushner <hello@matchai.me>\"]
+depends: [
+  \"ocaml\" {>= \"4.08\"}
+  \"dune\" {>= \"3.0\" & build}
+]";
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(extract_opam_version(opam).ok(), expected_version);
+
+        let opam_without_version = "opam-version: \"2.0\"
+name: \"starship\"";
+
+        let expected_version = None;
+        assert_eq!(
+            extract_opam_version(opam_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_get_opam_cli_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = get_opam_cli_version(dir.path(), |command, command_dir| {
+            assert_eq!(command, "opam show --field=version .");
+            assert_eq!(command_dir, dir.path());
+            Some("\"0.1.0\"\n".to_string())
+        });
+        assert_eq!(actual, Ok("v0.1.0".to_string()));
+
+        let unquoted = get_opam_cli_version(dir.path(), |_, _| Some("0.1.0\n".to_string()));
+        assert_eq!(unquoted, Ok("v0.1.0".to_string()));
+
+        let failed = get_opam_cli_version(dir.path(), |_, _| None);
+        assert_eq!(failed, Err(PackageError::NoVersionField));
+
+        let empty = get_opam_cli_version(dir.path(), |_, _| Some("\n".to_string()));
+        assert_eq!(empty, Err(PackageError::NoVersionField));
+        dir.close()
+    }
+
+    #[test]
+    fn test_opam_without_version_does_not_run_opam_by_default() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let opam = "opam-version: \"2.0\"\nname: \"starship\"\n";
+
+        let config = PackageConfig::new();
+        assert!(!config.resolve_opam_version);
+        assert_eq!(
+            get_opam_version(opam, dir.path(), &config),
+            Err(PackageError::NoVersionField)
+        );
+
+        // opam is only asked when the file has no version
+        let with_version = "opam-version: \"2.0\"\nversion: \"0.1.0\"\n";
+        let mut config = PackageConfig::new();
+        config.resolve_opam_version = true;
+        assert_eq!(
+            get_opam_version(with_version, dir.path(), &config),
+            Ok("v0.1.0".to_string())
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_hpack_version() {
+        let package_yaml = "\
+name: starship
+version: 0.1.0
+dependencies:
+  - base >= 4.7 && < 5
+  - name: text
+    version: 2.0.2
+library:
+  source-dirs: src
+";
+        assert_eq!(
+            extract_hpack_version(package_yaml),
+            Ok("v0.1.0".to_string())
+        );
+
+        let package_yaml = "name: starship\ndependencies:\n  - name: text\n    version: 2.0.2\n";
+        assert_eq!(
+            extract_hpack_version(package_yaml),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn test_extract_cabal_version() {
+        let cabal = "\
+cabal-version: 2.4
+name:          starship
+Version:       0.1.0.0
+
+library
+  build-depends: base ^>=4.14
+";
+        assert_eq!(extract_cabal_version(cabal), Ok("v0.1.0.0".to_string()));
+        assert_eq!(
+            extract_cabal_version("cabal-version: 2.4\nname: starship\n"),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn folder_with_hpack_package_and_generated_cabal() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "package.yaml", "name: starship\nversion: 0.1.0\n")?;
+        fill_config(&dir, "starship.cabal", "name: starship\nversion: 0.0.9\n")?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_pubspec_version() {
+        let app_pubspec = "\
+name: starship_app
+publish_to: none
+environment:
+  sdk: '>=3.0.0 <4.0.0'
+dependencies:
+  starship:
+    git:
+      url: https://github.com/starship/starship.git
+      ref: v0.1.0
+  local_package:
+    path: ../local_package
+";
+        let config = PackageConfig::new();
+        assert_eq!(
+            ManifestKind::Pubspec.extract_version(app_pubspec, Path::new("."), &config),
+            Err(PackageError::NoVersionField)
+        );
+
+        let published = "name: starship\nversion: 0.1.0\n";
+        assert_eq!(
+            ManifestKind::Pubspec.extract_version(published, Path::new("."), &config),
+            Ok("v0.1.0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_is_unpublishable_pubspec() {
+        assert!(!is_unpublishable_pubspec(
+            "name: starship\nversion: 0.1.0\n"
+        ));
+        assert!(is_unpublishable_pubspec(
+            "name: starship\nversion: 0.1.0\npublish_to: none\n"
+        ));
+        assert!(!is_unpublishable_pubspec(
+            "name: starship\nversion: 0.1.0\npublish_to: https://pub.example.com\n"
+        ));
+        assert!(!is_unpublishable_pubspec("name: [starship"));
+    }
+
+    #[test]
+    fn folder_with_unpublishable_pubspec() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "pubspec.yaml",
+            "name: starship\nversion: 0.1.0\npublish_to: none\n",
+        )?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                hide_unpublishable = true
+            })
+            .collect();
+        assert_eq!(None, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_plist_version() {
+        let info_plist = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>CFBundleName</key>
+	<string>Starship</string>
+	<key>CFBundleShortVersionString</key>
+	<string>0.1.0</string>
+	<key>CFBundleVersion</key>
+	<string>42</string>
+</dict>
+</plist>"#;
+        assert_eq!(extract_plist_version(info_plist), Ok("v0.1.0".to_string()));
+
+        // Xcode substitutes build settings when building the app
+        let templated = info_plist.replace("0.1.0", "$(MARKETING_VERSION)");
+        assert_eq!(
+            extract_plist_version(&templated),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn folder_with_binary_info_plist() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("Info.plist"),
+            b"bplist00\xd1\x01\x02_\x10\x1aCFBundleShortVersionString",
+        )?;
+
+        assert_eq!(
+            get_package_version(dir.path(), &PackageConfig::new()),
+            Err(PackageError::NoVersionField)
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_bazel_module_version() {
+        let module_bazel = r#"module(
+    name = "starship",
+    version = "0.1.0",
+    compatibility_level = 1,
+)
+
+bazel_dep(name = "rules_rust", version = "0.40.0")
+"#;
+        assert_eq!(
+            extract_bazel_module_version(module_bazel),
+            Ok("v0.1.0".to_string())
+        );
+
+        let unversioned = "module(name = \"starship\")\n\nbazel_dep(name = \"rules_rust\", version = \"0.40.0\")\n";
+        assert_eq!(
+            extract_bazel_module_version(unversioned),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn test_extract_bazel_build_version() {
+        let build_bazel = r#"load("@rules_pkg//pkg:tar.bzl", "pkg_tar")
+load("@pip//:requirements.bzl", "requirement")
+
+py_library(
+    name = "starship_lib",
+    deps = [requirement("requests")],
+)
+
+pkg_tar(
+    name = "starship",
+    srcs = [":starship_lib"],
+    version = "0.1.0",
+)
+"#;
+        assert_eq!(
+            extract_bazel_build_version(build_bazel),
+            Ok("v0.1.0".to_string())
+        );
+
+        let wheel = r#"py_wheel(
+    name = "starship_wheel",
+    distribution = "starship",
+    deps = [requirement("requests")],
+    version = "0.1.0",
+)
+"#;
+        assert_eq!(extract_bazel_build_version(wheel), Ok("v0.1.0".to_string()));
+
+        let pinned_dependency = concat!(
+            "maven.artifact(group = \"com.google.guava\", artifact = \"guava\", ",
+            "version = \"31.1-jre\")\n"
+        );
+        assert_eq!(
+            extract_bazel_build_version(pinned_dependency),
+            Err(PackageError::NotFound)
+        );
+
+        let multi_line_dependency = r#"maven.artifact(
+    artifact = "guava",
+    group = "com.google.guava",
+    version = "31.1-jre",
+)
+"#;
+        assert_eq!(
+            extract_bazel_build_version(multi_line_dependency),
+            Err(PackageError::NotFound)
+        );
+    }
+
+    #[test]
+    fn folder_with_build_without_packaging_rule_and_version_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "BUILD",
+            "cc_binary(\n    name = \"starship\",\n    srcs = [\"main.cc\"],\n)\n",
+        )?;
+        fill_config(&dir, "VERSION", "0.1.0\n")?;
+
+        let actual = get_package_version(dir.path(), &PackageConfig::new());
+        assert_eq!(
+            actual.map(|package| package.version),
+            Ok("v0.1.0".to_string())
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_module_bazel_and_build_bazel() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "BUILD.bazel",
+            "pkg_deb(\n    name = \"starship\",\n    version = \"0.2.0\",\n)\n",
+        )?;
+        assert_eq!(
+            get_package_version(dir.path(), &PackageConfig::new()).map(|package| package.version),
+            Ok("v0.2.0".to_string())
+        );
+
+        fill_config(
+            &dir,
+            "MODULE.bazel",
+            "module(name = \"starship\", version = \"0.1.0\")\n",
+        )?;
+        assert_eq!(
+            get_package_version(dir.path(), &PackageConfig::new()).map(|package| package.version),
+            Ok("v0.1.0".to_string())
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_rpm_spec_version() {
+        let spec = "\
+Name:           starship
+Version:        0.1.0
+Release:        1%{?dist}
+Summary:        The minimal, blazing-fast, and infinitely customizable prompt
+
+%changelog
+* Mon Jan 01 2024 Starship <starship@example.com> - 0.0.9-1
+";
+        assert_eq!(extract_rpm_spec_version(spec), Ok("v0.1.0".to_string()));
+
+        for &macro_version in &["%{version}", "%{major}.%{minor}", "1.%{minor}"] {
+            let spec = format!("Name: starship\nVersion: {}\n", macro_version);
+            assert_eq!(
+                extract_rpm_spec_version(&spec),
+                Err(PackageError::NoVersionField),
+                "{}",
+                macro_version
+            );
+        }
+
+        let pyinstaller_spec = "\
+# -*- mode: python ; coding: utf-8 -*-
+a = Analysis(['app.py'], pathex=[], binaries=[])
+pyz = PYZ(a.pure)
+exe = EXE(pyz, a.scripts, name='app', console=True)
+";
+        assert_eq!(
+            extract_rpm_spec_version(pyinstaller_spec),
+            Err(PackageError::NotFound)
+        );
+    }
+
+    #[test]
+    fn folder_with_pyinstaller_spec_and_version_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "app.spec", "a = Analysis(['app.py'])\n")?;
+        fill_config(&dir, "VERSION", "0.1.0\n")?;
+
+        let actual = get_package_version(dir.path(), &PackageConfig::new());
+        assert_eq!(
+            actual.map(|package| package.version),
+            Ok("v0.1.0".to_string())
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_debian_changelog_version() {
+        let changelog = "\
+starship (0.1.0-2) unstable; urgency=medium
+
+  * Rebuild against the new toolchain.
+
+ -- Starship <starship@example.com>  Tue, 02 Jan 2024 12:00:00 +0000
+
+starship (0.1.0-1) unstable; urgency=medium
+
+  * Initial release.
+
+ -- Starship <starship@example.com>  Mon, 01 Jan 2024 12:00:00 +0000
+";
+        assert_eq!(
+            extract_debian_changelog_version(changelog, false),
+            Ok("v0.1.0-2".to_string())
+        );
+        assert_eq!(
+            extract_debian_changelog_version(changelog, true),
+            Ok("v0.1.0".to_string())
+        );
+
+        // Native packages have no Debian revision to strip
+        let native = "starship (1:0.1.0) unstable; urgency=medium\n";
+        assert_eq!(
+            extract_debian_changelog_version(native, true),
+            Ok("v1:0.1.0".to_string())
+        );
+
+        assert_eq!(
+            extract_debian_changelog_version("  * Initial release.\n", false),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn test_debian_changelog_strip_revision() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "debian/changelog",
+            "starship (0.1.0-3) unstable; urgency=medium\n",
+        )?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                strip_debian_revision = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_rockspec_version() {
+        let rockspec = r#"
+package = "starship"
+version = "0.1.0-1"
+source = {
+   url = "git+https://github.com/starship/starship.git",
+   tag = "v0.1.0",
+}
+"#;
+        assert_eq!(
+            extract_rockspec_version(rockspec),
+            Ok("v0.1.0-1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_newest_rockspec() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        // Alphabetically, `starship-1.10-1` comes before `starship-1.9-1`
+        fill_config(
+            &dir,
+            "starship-1.9-1.rockspec",
+            "package = \"starship\"\nversion = \"1.9-1\"\n",
+        )?;
+        fill_config(
+            &dir,
+            "starship-1.10-1.rockspec",
+            "package = \"starship\"\nversion = \"1.10-1\"\n",
+        )?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v1.10-1")
+        ));
+        assert_eq!(actual, expected);
+
+        fill_config(
+            &dir,
+            "starship-1.10-2.rockspec",
+            "package = \"starship\"\nversion = \"1.10-2\"\n",
+        )?;
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v1.10-2")
+        ));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn test_file_name_version() {
+        for &(file_name, expected) in &[
+            ("starship-1.10.0-2.rockspec", &[1, 10, 0, 2][..]),
+            ("lua-cjson-2.1.0-1.rockspec", &[2, 1, 0, 1][..]),
+            ("starship-scm-1.rockspec", &[0, 1][..]),
+        ] {
+            assert_eq!(
+                file_name_version(Path::new(file_name)),
+                expected,
+                "{}",
+                file_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract_plain_version() {
+        assert_eq!(extract_plain_version("0.1.0\n"), Ok("v0.1.0".to_string()));
+        assert_eq!(
+            extract_plain_version("\n  v0.1.0  \n"),
+            Ok("v0.1.0".to_string())
+        );
+        assert_eq!(extract_plain_version(""), Err(PackageError::NoVersionField));
+        assert_eq!(
+            extract_plain_version("not a version\n"),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn folder_with_custom_version_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, ".release-version", "0.1.0\n")?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        assert_eq!(actual, None);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                version_file_names = ["VERSION", ".release-version"]
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_cabal_project() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "cabal.project",
+            "packages: starship-core/\n          starship-cli/starship-cli.cabal\n\ntests: True\n",
+        )?;
+        fill_config(
+            &dir,
+            "starship-core/starship-core.cabal",
+            "cabal-version: 2.4\nname: starship-core\nversion: 0.1.0\n",
+        )?;
+        fill_config(
+            &dir,
+            "starship-cli/starship-cli.cabal",
+            "cabal-version: 2.4\nname: starship-cli\nversion: 0.2.0\n",
+        )?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+
+        // Inside a package, its own `.cabal` is found before the `cabal.project` of the root
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path().join("starship-cli"))
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.2.0")
+        ));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn test_get_cabal_project_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "packages/starship/starship.cabal",
+            "name: starship\nversion: 0.1.0\n",
+        )?;
+
+        for &cabal_project in &[
+            "packages: packages/*/\n",
+            "packages:\n  packages/starship/starship.cabal\n",
+            "packages: packages/starship, packages/other\n",
+        ] {
+            assert_eq!(
+                get_cabal_project_version(cabal_project, dir.path()),
+                Ok("v0.1.0".to_string()),
+                "{}",
+                cabal_project
+            );
+        }
+        assert_eq!(
+            get_cabal_project_version("packages: missing/\n", dir.path()),
+            Err(PackageError::NoVersionField)
+        );
+        assert_eq!(
+            get_cabal_project_version("with-compiler: ghc-9.4\n", dir.path()),
+            Err(PackageError::NoVersionField)
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_sfdx_version() {
+        let sfdx_project = json::json!({
+            "packageDirectories": [
+                { "path": "force-app", "default": true, "package": "Starship", "versionNumber": "0.1.0.NEXT" },
+                { "path": "extensions", "package": "Starship Extensions", "versionNumber": "0.2.0.LATEST" },
+                { "path": "unpackaged" }
+            ],
+            "namespace": "",
+            "sourceApiVersion": "59.0"
+        });
+        assert_eq!(
+            extract_sfdx_version(&sfdx_project.to_string()),
+            Ok("v0.1.0".to_string())
+        );
+
+        let second_only = json::json!({
+            "packageDirectories": [
+                { "path": "unpackaged" },
+                { "path": "force-app", "versionNumber": "0.2.0.LATEST" }
+            ]
+        });
+        assert_eq!(
+            extract_sfdx_version(&second_only.to_string()),
+            Ok("v0.2.0".to_string())
+        );
+
+        let unpackaged = json::json!({
+            "packageDirectories": [{ "path": "force-app", "default": true }],
+            "sourceApiVersion": "59.0"
+        });
+        assert_eq!(
+            extract_sfdx_version(&unpackaged.to_string()),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn test_extract_citation_cff_version() {
+        let citation = r#"cff-version: 1.2.0
+message: "If you use this software, please cite it as below."
+title: "Starship: the minimal, blazing-fast, and infinitely customizable prompt"
+authors:
+  - family-names: Doe
+    given-names: Jane
+    orcid: "https://orcid.org/0000-0000-0000-0000"
+  - name: "The Starship Contributors"
+version: 0.1.0
+doi: 10.5281/zenodo.1234
+date-released: 2024-01-01
+"#;
+        assert_eq!(
+            extract_citation_cff_version(citation),
+            Ok("v0.1.0".to_string())
+        );
+
+        let without_version = citation.replace("version: 0.1.0\n", "");
+        assert_eq!(
+            extract_citation_cff_version(&without_version),
+            Err(PackageError::NotFound)
+        );
+    }
+
+    #[test]
+    fn test_extract_dockerfile_version() {
+        let oci_label = r#"FROM alpine:3.19
+LABEL version="0.2.0"
+LABEL org.opencontainers.image.title="starship" \
+      org.opencontainers.image.version="0.1.0"
+RUN apk add --no-cache bash
+"#;
+        assert_eq!(
+            extract_dockerfile_version(oci_label),
+            Ok("v0.1.0".to_string())
+        );
+
+        let version_label = "FROM alpine:3.19\nLABEL maintainer=starship version=0.1.0\n";
+        assert_eq!(
+            extract_dockerfile_version(version_label),
+            Ok("v0.1.0".to_string())
+        );
+
+        let without_labels = "FROM alpine:3.19\nLABEL maintainer=\"starship\"\nENV VERSION=0.1.0\n";
+        assert_eq!(
+            extract_dockerfile_version(without_labels),
+            Err(PackageError::NotFound)
+        );
+    }
+
+    #[test]
+    fn test_extract_yaml_version() {
+        let service_yaml = "name: starship
+version: 0.1.0
+owners:
+  - team-prompt
+";
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(extract_yaml_version(service_yaml).ok(), expected_version);
+
+        let expected_version = Some("v1.2".to_string());
+        assert_eq!(extract_yaml_version("version: 1.2").ok(), expected_version);
+
+        let expected_version = None;
+        assert_eq!(
+            extract_yaml_version("name: starship").ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_custom_version() {
+        let toml_manifest = "[pkg]\nname = \"starship\"\nversion = \"0.1.0\"\n";
+        assert_eq!(
+            extract_custom_version(CustomFormat::Toml, toml_manifest, "pkg.version"),
+            Ok("v0.1.0".to_string())
+        );
+        assert_eq!(
+            extract_custom_version(CustomFormat::Toml, toml_manifest, "version"),
+            Err(PackageError::NoVersionField)
+        );
+
+        let json_manifest = "{ \"meta\": { \"release\": { \"version\": \"0.1.0\" } } }";
+        assert_eq!(
+            extract_custom_version(CustomFormat::Json, json_manifest, "meta.release.version"),
+            Ok("v0.1.0".to_string())
+        );
+
+        let yaml_manifest = "service:\n  name: starship\n  version: 0.1.0\n";
+        assert_eq!(
+            extract_custom_version(CustomFormat::Yaml, yaml_manifest, "service.version"),
+            Ok("v0.1.0".to_string())
+        );
+
+        let text_manifest = "STARSHIP_RELEASE=0.1.0\n";
+        assert_eq!(
+            extract_custom_version(CustomFormat::Regex, text_manifest, "RELEASE=(\\S+)"),
+            Ok("v0.1.0".to_string())
+        );
+
+        let text_manifest = "STARSHIP_RELEASE=$NEXT_RELEASE\n";
+        assert_eq!(
+            extract_custom_version(CustomFormat::Regex, text_manifest, "RELEASE=(\\S+)"),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn test_extract_cargo_kind() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let lib_crate = toml::toml! {
+            [package]
+            name = "starship"
+            version = "0.1.0"
+
+            [lib]
+            name = "starship"
+        }
+        .to_string();
+
+        let expected_kind = Some(PackageKind::Lib);
+        assert_eq!(extract_cargo_kind(&lib_crate, dir.path()), expected_kind);
+
+        let bin_crate = toml::toml! {
+            [package]
+            name = "starship"
+            version = "0.1.0"
+
+            [[bin]]
+            name = "starship"
+        }
+        .to_string();
+
+        let expected_kind = Some(PackageKind::Bin);
+        assert_eq!(extract_cargo_kind(&bin_crate, dir.path()), expected_kind);
+
+        let expected_kind = None;
+        assert_eq!(extract_cargo_kind(CARGO_TOML, dir.path()), expected_kind);
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_package_kind() {
+        let package_with_bin = json::json!({
+            "name": "spacefish",
+            "version": "0.1.0",
+            "bin": { "spacefish": "./cli.js" }
+        })
+        .to_string();
+
+        let expected_kind = Some(PackageKind::Bin);
+        assert_eq!(extract_package_kind(&package_with_bin), expected_kind);
+
+        let package_without_bin = json::json!({
+            "name": "spacefish",
+            "version": "0.1.0"
+        })
+        .to_string();
+
+        let expected_kind = None;
+        assert_eq!(extract_package_kind(&package_without_bin), expected_kind);
+    }
+
+    /// A minimal manifest of the given kind declaring `version`
+    fn manifest_fixture(manifest: ManifestKind, version: &str) -> (&'static str, String) {
+        let file_name = match manifest.file() {
+            ManifestFile::Name(file_name) => file_name,
+            ManifestFile::AnyName(file_names) => file_names[0],
+            ManifestFile::Extension("podspec") => "Starship.podspec",
+            ManifestFile::Extension("gemspec") => "starship.gemspec",
+            ManifestFile::Extension("opam") => "starship.opam",
+            ManifestFile::Extension("csproj") => "Starship.csproj",
+            ManifestFile::Extension("nuspec") => "Starship.nuspec",
+            ManifestFile::ExtensionIn(_, "rb") => "starship.rb",
+            ManifestFile::Extension("cabal") => "starship.cabal",
+            ManifestFile::Extension("spec") => "starship.spec",
+            ManifestFile::NewestExtension("rockspec") => "starship-0.1.0-1.rockspec",
+            ManifestFile::Extension(extension) | ManifestFile::ExtensionIn(_, extension) => {
+                panic!("No fixture for *.{}", extension)
+            }
+            ManifestFile::Pattern(pattern) => panic!("No fixture for {}", pattern),
+            ManifestFile::NewestExtension(extension) => panic!("No fixture for *.{}", extension),
+        };
+        let file_contents = match manifest {
+            ManifestKind::Cargo => format!("[package]\nversion = \"{}\"\n", version),
+            ManifestKind::Npm
+            | ManifestKind::Deno
+            | ManifestKind::Composer
+            | ManifestKind::Vcpkg
+            | ManifestKind::Elm => json::json!({ "version": version }).to_string(),
+            ManifestKind::PyProject => format!("[tool.poetry]\nversion = \"{}\"\n", version),
+            ManifestKind::Gradle => format!("version '{}'", version),
+            ManifestKind::Maven => format!("<project><version>{}</version></project>", version),
+            ManifestKind::Ant => format!(
+                "<project><property name=\"version\" value=\"{}\"/></project>",
+                version
+            ),
+            ManifestKind::Julia => format!("version = \"{}\"\n", version),
+            ManifestKind::Gleam => format!("name = \"starship\"\nversion = \"{}\"\n", version),
+            ManifestKind::Mix => format!("version: \"{}\"", version),
+            ManifestKind::Racket => format!("(define version \"{}\")", version),
+            ManifestKind::WebExtension => {
+                json::json!({ "manifest_version": 2, "version": version }).to_string()
+            }
+            ManifestKind::GrafanaPlugin => json::json!({
+                "type": "panel",
+                "id": "starship-panel",
+                "info": { "version": version }
+            })
+            .to_string(),
+            ManifestKind::Zig => format!("const version = \"{}\";", version),
+            ManifestKind::CocoaPods | ManifestKind::Gemspec => {
+                format!("s.version = \"{}\"", version)
+            }
+            ManifestKind::Chef => format!("name 'starship'\nversion '{}'\n", version),
+            ManifestKind::Homebrew => {
+                format!("class Starship < Formula\n  version \"{}\"\nend\n", version)
+            }
+            ManifestKind::Dune => format!("(lang dune 3.0)\n(version {})", version),
+            ManifestKind::Opam => format!("opam-version: \"2.0\"\nversion: \"{}\"", version),
+            ManifestKind::Hpack => format!("name: starship\nversion: {}\n", version),
+            ManifestKind::Cabal => {
+                format!("cabal-version: 2.4\nname: starship\nversion: {}\n", version)
+            }
+            ManifestKind::CabalProject => unreachable!("cabal.project has no fixture"),
+            ManifestKind::Pubspec => format!("name: starship\nversion: {}\n", version),
+            ManifestKind::Dotnet => format!(
+                "<Project><PropertyGroup><Version>{}</Version></PropertyGroup></Project>",
+                version
+            ),
+            ManifestKind::Nuspec => format!(
+                "<package><metadata><version>{}</version></metadata></package>",
+                version
+            ),
+            ManifestKind::Plist => format!(
+                concat!(
+                    "<plist><dict><key>CFBundleShortVersionString</key>",
+                    "<string>{}</string></dict></plist>"
+                ),
+                version
+            ),
+            ManifestKind::Swift => unreachable!("Package.swift has no fixture"),
+            ManifestKind::BazelModule => format!(
+                "module(\n    name = \"starship\",\n    version = \"{}\",\n)\n",
+                version
+            ),
+            ManifestKind::Bazel => format!(
+                "pkg_tar(\n    name = \"starship\",\n    version = \"{}\",\n)\n",
+                version
+            ),
+            ManifestKind::RpmSpec => format!(
+                "Name: starship\nVersion: {}\nRelease: 1%{{?dist}}\n",
+                version
+            ),
+            ManifestKind::DebianChangelog => {
+                format!("starship ({}) unstable; urgency=medium\n", version)
+            }
+            ManifestKind::Rockspec => {
+                format!("package = \"starship\"\nversion = \"{}\"\n", version)
+            }
+            ManifestKind::Sfdx => json::json!({
+                "packageDirectories": [
+                    { "path": "force-app", "versionNumber": format!("{}.NEXT", version) }
+                ]
+            })
+            .to_string(),
+            ManifestKind::CitationCff => format!(
+                "cff-version: 1.2.0\ntitle: Starship\nversion: {}\n",
+                version
+            ),
+            ManifestKind::Dockerfile => format!(
+                "FROM scratch\nLABEL org.opencontainers.image.version=\"{}\"\n",
+                version
+            ),
+            ManifestKind::Yaml | ManifestKind::Custom => format!("version: {}\n", version),
+            ManifestKind::VersionFile => format!("{}\n", version),
+        };
+
+        (file_name, file_contents)
+    }
+
+    #[test]
+    fn test_supported_manifests_priority() -> io::Result<()> {
+        assert!(!supported_manifests().is_empty());
+
+        // SwiftPM manifests don't declare a version, see `folder_with_version_specific_swift_manifest`,
+        // and `cabal.project` points to other manifests, see `folder_with_cabal_project`
+        let manifests: Vec<ManifestKind> = supported_manifests()
+            .iter()
+            .copied()
+            .filter(|&manifest| {
+                manifest != ManifestKind::Swift && manifest != ManifestKind::CabalProject
+            })
+            .collect();
+
+        let dir = tempfile::tempdir()?;
+        for (index, &manifest) in manifests.iter().enumerate() {
+            let (file_name, file_contents) = manifest_fixture(manifest, &format!("{}.0.0", index));
+            fill_config(&dir, file_name, &file_contents)?;
+        }
+
+        // Removing the detected manifest each time should reveal the next one in order
+        for (index, &manifest) in manifests.iter().enumerate() {
+            let (file_name, _) = manifest_fixture(manifest, "");
+            let expected = Ok(PackageVersion {
+                version: format!("v{}.0.0", index),
+                manifest,
+                name: None,
+                dir: dir.path().to_path_buf(),
+                file: dir.path().join(file_name),
+            });
+            assert_eq!(
+                get_package_version(dir.path(), &PackageConfig::new()),
+                expected
+            );
+            fs::remove_file(dir.path().join(file_name))?;
+        }
+
+        assert_eq!(
+            get_package_version(dir.path(), &PackageConfig::new()),
+            Err(PackageError::NotFound)
+        );
+        dir.close()
+    }
+
+    /// Reads `version` from a `mock` manifest in any directory, counting how often it is asked to
+    struct MockExtractor {
+        version: &'static str,
+        extracted: Rc<Cell<usize>>,
+    }
+
+    impl ManifestExtractor for MockExtractor {
+        fn detect(&self, files: &DirFiles) -> Result<Option<PathBuf>, PackageError> {
+            Ok(Some(files.dir.join("mock")))
+        }
+
+        fn extract(
+            &self,
+            file: PathBuf,
+            dir: &Path,
+            _config: &PackageConfig,
+        ) -> Result<PackageVersion, PackageError> {
+            assert_eq!(file, dir.join("mock"));
+            self.extracted.set(self.extracted.get() + 1);
+            Ok(PackageVersion {
+                version: self.version.to_string(),
+                manifest: ManifestKind::Custom,
+                name: None,
+                dir: dir.to_path_buf(),
+                file,
+            })
+        }
+    }
+
+    #[test]
+    fn test_manifest_extractor_priority() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let config = PackageConfig::new();
+        let extracted = Rc::new(Cell::new(0));
+        let mock = || -> Box<dyn ManifestExtractor> {
+            Box::new(MockExtractor {
+                version: "v0.2.0",
+                extracted: Rc::clone(&extracted),
+            })
+        };
+
+        let extractors = vec![mock(), Box::new(ManifestKind::Cargo)];
+        let actual = get_package_version_from(dir.path(), &config, &extractors)
+            .map(|package| package.version);
+        assert_eq!(actual, Ok("v0.2.0".to_string()));
+        assert_eq!(extracted.get(), 1);
+
+        // Extractors after the one that found a version aren't asked
+        let extractors = vec![Box::new(ManifestKind::Cargo), mock()];
+        let actual = get_package_version_from(dir.path(), &config, &extractors)
+            .map(|package| package.version);
+        assert_eq!(actual, Ok("v0.1.0".to_string()));
+        assert_eq!(extracted.get(), 1);
+
+        // Nor are extractors that don't detect their manifest
+        let extractors = vec![Box::new(ManifestKind::Npm), mock()];
+        let actual = get_package_version_from(dir.path(), &config, &extractors)
+            .map(|package| package.version);
+        assert_eq!(actual, Ok("v0.2.0".to_string()));
+        assert_eq!(extracted.get(), 2);
+        dir.close()
+    }
+
+    #[test]
+    fn test_manifest_precedence() -> io::Result<()> {
+        const PYPROJECT_TOML: &str = "[project]\nname = \"starship\"\nversion = \"0.1.0\"\n";
+        const SETUP_CFG: &str = "[metadata]\nname = starship\nversion = 0.2.0\n";
+        const BUILD_GRADLE: &str = "version '0.1.0'";
+        const POM_XML: &str = "<project><version>0.2.0</version></project>";
+
+        let config = PackageConfig {
+            detect_regex: vec![CustomRegexManifest {
+                file: "VERSION",
+                pattern: "(.+)",
+            }],
+            ..PackageConfig::new()
+        };
+
+        // The files of each case, and the manifest kind and version expected to win
+        type Case<'a> = (&'a [(&'a str, &'a str)], Option<(ManifestKind, &'a str)>);
+        let cases: &[Case] = &[
+            (
+                &[
+                    ("Cargo.toml", CARGO_TOML),
+                    ("package.json", r#"{"version": "0.2.0"}"#),
+                ],
+                Some((ManifestKind::Cargo, "v0.1.0")),
+            ),
+            // setup.cfg isn't supported, so it never wins
+            (
+                &[("pyproject.toml", PYPROJECT_TOML), ("setup.cfg", SETUP_CFG)],
+                Some((ManifestKind::PyProject, "v0.1.0")),
+            ),
+            (&[("setup.cfg", SETUP_CFG)], None),
+            (
+                &[("build.gradle", BUILD_GRADLE), ("pom.xml", POM_XML)],
+                Some((ManifestKind::Gradle, "v0.1.0")),
+            ),
+            (
+                &[("pom.xml", POM_XML)],
+                Some((ManifestKind::Maven, "v0.2.0")),
+            ),
+            // Manifests registered in the config are only read when no built-in one exists
+            (
+                &[("Cargo.toml", CARGO_TOML), ("VERSION", "0.2.0")],
+                Some((ManifestKind::Cargo, "v0.1.0")),
+            ),
+            (
+                &[("VERSION", "0.2.0")],
+                Some((ManifestKind::Custom, "v0.2.0")),
+            ),
+            // Plain version files are only read when nothing else declares a version
+            (
+                &[
+                    ("package.json", r#"{"version": "0.1.0"}"#),
+                    ("version.txt", "0.2.0"),
+                ],
+                Some((ManifestKind::Npm, "v0.1.0")),
+            ),
+            (
+                &[("version.txt", "0.2.0\n")],
+                Some((ManifestKind::VersionFile, "v0.2.0")),
+            ),
+        ];
+
+        for (files, expected) in cases {
+            let dir = tempfile::tempdir()?;
+            for (file_name, contents) in files.iter() {
+                fill_config(&dir, file_name, contents)?;
+            }
+
+            let actual = get_package_version(dir.path(), &config)
+                .ok()
+                .map(|package| (package.manifest, package.version));
+            let expected = expected.map(|(manifest, version)| (manifest, version.to_string()));
+            assert_eq!(actual, expected, "{:?}", files);
+            dir.close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn folder_with_generic_manifest_json_and_podspec() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "manifest.json", "{ \"version\": \"0.2.0\" }")?;
+        fill_config(&dir, "Starship.podspec", "s.version = \"0.1.0\"")?;
+
+        let actual =
+            get_package_version(dir.path(), &PackageConfig::new()).map(|package| package.manifest);
+        let expected = Ok(ManifestKind::CocoaPods);
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_without_manifest() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_cargo_toml() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_cargo_toml_and_custom_config() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                symbol = "🎁 "
+                style = "green"
+            })
+            .collect();
+        let expected = Some(format!("is {} ", Color::Green.paint("🎁 v0.1.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_get_package_version_without_manifest() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let expected = Err(PackageError::NotFound);
+        assert_eq!(
+            get_package_version(dir.path(), &PackageConfig::new()),
+            expected
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_get_package_version_with_broken_manifest() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "package.json", "{ \"version\": ")?;
+
+        let expected = Err(PackageError::ParseFailed);
+        assert_eq!(
+            get_package_version(dir.path(), &PackageConfig::new()),
+            expected
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_get_package_version_with_versionless_manifest() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", "[package]\nname = \"starship\"\n")?;
+
+        let expected = Err(PackageError::NoVersionField);
+        assert_eq!(
+            get_package_version(dir.path(), &PackageConfig::new()),
+            expected
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_lib_crate_and_symbol_variants() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        fs::create_dir(dir.path().join("src"))?;
+        fill_config(&dir, "src/lib.rs", "")?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                symbol_variants = { lib = "📚 ", bin = "🚀 " }
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📚 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_unknown_kind_and_default_symbol_variant() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                symbol_variants = { bin = "🚀 ", default = "🎁 " }
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("🎁 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_below_manifest() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let sub_dir = dir.path().join("src").join("modules");
+        fs::create_dir_all(&sub_dir)?;
+
+        let actual = ModuleRenderer::new("package").path(&sub_dir).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_below_manifest_beyond_max_search_depth() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let sub_dir = dir.path().join("a").join("b").join("c");
+        fs::create_dir_all(&sub_dir)?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(&sub_dir)
+            .config(toml::toml! {
+                [package]
+                max_search_depth = 2
+            })
+            .collect();
+        let expected = None;
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("package")
+            .path(&sub_dir)
+            .config(toml::toml! {
+                [package]
+                max_search_depth = 3
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_below_manifest_outside_repository() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let repo_dir = dir.path().join("repo");
+        fs::create_dir(&repo_dir)?;
+        git2::Repository::init(&repo_dir)?;
+
+        let actual = ModuleRenderer::new("package").path(&repo_dir).collect();
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn folder_in_symlinked_repository_below_manifest() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let repo_dir = dir.path().join("repo");
+        fs::create_dir(&repo_dir)?;
+        git2::Repository::init(&repo_dir)?;
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&repo_dir, &link)?;
+
+        // The walk stops at the root of the repository, even when it is reached through a symlink
+        let actual = ModuleRenderer::new("package").path(&link).collect();
+        assert_eq!(None, actual);
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn folder_with_placeholder_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "Cargo.toml",
+            "[package]\nname = \"starship\"\nversion = \"0.0.0\"\n",
+        )?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = None;
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                placeholder_versions = []
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.0.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_placeholder_version_in_tagged_repo() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tagged_repo(
+            "Cargo.toml",
+            "[package]\nname = \"starship\"\nversion = \"0.0.0\"\n",
+        )?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        assert_eq!(None, actual);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                resolve_git_version = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+
+        // The version from git is cached for the next prompt
+        let cached = cached_version(
+            &dir.path().join("Cargo.toml"),
+            &PackageConfig::new(),
+            || panic!("git queried despite a cached version"),
+        );
+        assert_eq!(cached, Some("v0.1.0".to_string()));
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn folder_with_custom_placeholder_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                placeholder_versions = ["0.0.0", "v0.1.0"]
+            })
+            .collect();
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_podspec() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "Starship.podspec",
+            "Pod::Spec.new do |s|\n  s.version = \"0.1.0\"\nend\n",
+        )?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_succeeding_when_command() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                when = "exit 0"
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_failing_when_command() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                when = "exit 1"
+            })
+            .collect();
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_broken_manifest_and_failing_when_command() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", "[package\nversion = \"0.1.0\"\n")?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                show_parse_errors = true
+                when = "exit 1"
+            })
+            .collect();
+        assert_eq!(None, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_versionless_manifest_and_failing_when_command() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "package.json", "{ \"name\": \"starship\" }")?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                show_when_no_version = true
+                when = "exit 1"
+            })
+            .collect();
+        assert_eq!(None, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_without_manifest_skips_when_command() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let marker = dir.path().join("when-ran");
+        let when = format!("touch {}", marker.display());
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                when = when
+            })
+            .collect();
+        assert_eq!(None, actual);
+        assert!(!marker.exists());
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_dune_project_and_opam() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "dune-project", "(lang dune 3.0)\n(version 0.2.0)\n")?;
+        fill_config(&dir, "starship.opam", "version: \"0.1.0\"\n")?;
+
+        let actual =
+            get_package_version(dir.path(), &PackageConfig::new()).map(|package| package.version);
+        assert_eq!(actual, Ok("v0.2.0".to_string()));
+
+        fill_config(&dir, "dune-project", "(lang dune 3.0)\n")?;
+
+        let actual =
+            get_package_version(dir.path(), &PackageConfig::new()).map(|package| package.version);
+        assert_eq!(actual, Ok("v0.1.0".to_string()));
+        dir.close()
+    }
+
+    #[test]
+    fn folder_without_manifest_with_version_command() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                version_command = "echo 0.1.0"
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn version_command_runs_in_base_dir() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "VERSION", "0.1.0\n")?;
+
+        let actual = get_command_version("cat VERSION", dir.path());
+        assert_eq!(actual, Some("v0.1.0".to_string()));
+        dir.close()
+    }
+
+    #[test]
+    fn folder_without_manifest_with_failing_version_command() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                version_command = "exit 1"
+            })
+            .collect();
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_readme_version_badge() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "README.md",
+            "# Starship\n\n![version](https://img.shields.io/badge/version-0.1.0--beta-blue)\n",
+        )?;
+
+        assert_eq!(
+            get_readme_badge_version(dir.path()),
+            Some("v0.1.0-beta".to_string())
+        );
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                readme_badge_fallback = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0-beta")
+        ));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        assert_eq!(None, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_readme_without_version_badge() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "README.md",
+            "# Starship\n\n![build](https://img.shields.io/badge/build-passing-green)\n",
+        )?;
+
+        assert_eq!(get_readme_badge_version(dir.path()), None);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_elm_package_json() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let elm_package_json = json::json!({
+            "version": "0.1.0",
+            "summary": "The cross-shell prompt",
+            "repository": "https://github.com/starship/starship.git",
+            "license": "ISC",
+            "source-directories": ["src"],
+            "exposed-modules": [],
+            "dependencies": { "elm-lang/core": "5.1.1 <= v < 6.0.0" },
+            "elm-version": "0.18.0 <= v < 0.19.0"
+        })
+        .to_string();
+        fill_config(&dir, "elm-package.json", &elm_package_json)?;
+
+        let actual = get_package_version(dir.path(), &PackageConfig::new());
+        assert_eq!(
+            actual,
+            Ok(PackageVersion {
+                version: "v0.1.0".to_string(),
+                manifest: ManifestKind::Elm,
+                name: None,
+                dir: dir.path().to_path_buf(),
+                file: dir.path().join("elm-package.json"),
+            })
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_elm_version() {
+        let application = json::json!({
+            "type": "application",
+            "source-directories": ["src"],
+            "elm-version": "0.19.1",
+            "dependencies": { "direct": { "elm/core": "1.0.5" }, "indirect": {} }
+        })
+        .to_string();
+        assert_eq!(
+            extract_elm_version(&application, false),
+            Err(PackageError::NoVersionField)
+        );
+        assert_eq!(
+            extract_elm_version(&application, true),
+            Ok("v0.19.1".to_string())
+        );
+
+        // Packages only constrain the compiler version, their own version is shown
+        let package = json::json!({
+            "type": "package",
+            "version": "0.2.0",
+            "elm-version": "0.19.0 <= v < 0.20.0"
+        })
+        .to_string();
+        assert_eq!(
+            extract_elm_version(&package, true),
+            Ok("v0.2.0".to_string())
+        );
+    }
+
+    #[test]
+    fn folder_with_elm_json_application_and_show_elm_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "elm.json",
+            r#"{"type": "application", "elm-version": "0.19.1"}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        assert_eq!(None, actual);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                show_elm_version = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.19.1")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_elm_json_package() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let elm_json = json::json!({
+            "type": "package",
+            "name": "starship/starship",
+            "version": "0.2.0",
+            "elm-version": "0.19.0 <= v < 0.20.0"
+        })
+        .to_string();
+        fill_config(&dir, "elm.json", &elm_json)?;
+        fill_config(&dir, "elm-package.json", "{ \"version\": \"0.1.0\" }")?;
+
+        let actual =
+            get_package_version(dir.path(), &PackageConfig::new()).map(|package| package.version);
+        assert_eq!(actual, Ok("v0.2.0".to_string()));
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_trimmed_version_prefix() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "package.json",
+            &json::json!({ "name": "starship", "version": "app@0.1.0" }).to_string(),
+        )?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                trim_prefix = "app@"
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    const STARSHIP_CSPROJ: &str = "<Project Sdk=\"Microsoft.NET.Sdk\">
+  <PropertyGroup>
+    <OutputType>Exe</OutputType>
+    <TargetFramework>net8.0</TargetFramework>
+    <Version>0.1.0</Version>
+  </PropertyGroup>
+</Project>";
+
+    #[test]
+    fn test_extract_dotnet_version() {
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(
+            extract_dotnet_version(STARSHIP_CSPROJ).ok(),
+            expected_version
+        );
+
+        let csproj_without_version = "<Project Sdk=\"Microsoft.NET.Sdk\">
+  <PropertyGroup>
+    <TargetFramework>net8.0</TargetFramework>
+  </PropertyGroup>
+</Project>";
+
+        let expected_version = None;
+        assert_eq!(
+            extract_dotnet_version(csproj_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn test_extract_dotnet_version_property_reference() {
+        let csproj = |properties: &str| {
+            format!(
+                "<Project Sdk=\"Microsoft.NET.Sdk\">\n  <PropertyGroup>\n{}  </PropertyGroup>\n</Project>",
+                properties
+            )
+        };
+
+        let same_file =
+            csproj("    <MyVersion>1.2.3</MyVersion>\n    <Version>$(MyVersion)</Version>\n");
+        assert_eq!(extract_dotnet_version(&same_file), Ok("v1.2.3".to_string()));
+
+        let nested = csproj(
+            "    <Major>1</Major>\n    <MyVersion>$(Major).2.3</MyVersion>\n    <VersionPrefix>$(MyVersion)</VersionPrefix>\n    <VersionSuffix>rc.$(Major)</VersionSuffix>\n",
+        );
+        assert_eq!(
+            extract_dotnet_version(&nested),
+            Ok("v1.2.3-rc.1".to_string())
+        );
+
+        // e.g. defined in an imported `Directory.Build.props`
+        let external = csproj("    <Version>$(MyVersion)</Version>\n");
+        assert_eq!(
+            extract_dotnet_version(&external),
+            Err(PackageError::NoVersionField)
+        );
+
+        let cyclic = csproj("    <A>$(B)</A>\n    <B>$(A)</B>\n    <Version>$(A)</Version>\n");
+        assert_eq!(
+            extract_dotnet_version(&cyclic),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn test_extract_dotnet_version_prefix_and_suffix() {
+        let csproj = |properties: &str| {
+            format!(
+                "<Project Sdk=\"Microsoft.NET.Sdk\">\n  <PropertyGroup>\n{}  </PropertyGroup>\n</Project>",
+                properties
+            )
+        };
+
+        let prefix_only = csproj("    <VersionPrefix>1.2.3</VersionPrefix>\n");
+        assert_eq!(
+            extract_dotnet_version(&prefix_only),
+            Ok("v1.2.3".to_string())
+        );
+
+        let prefix_and_suffix = csproj(
+            "    <VersionPrefix>1.2.3</VersionPrefix>\n    <VersionSuffix>beta.1</VersionSuffix>\n",
+        );
+        assert_eq!(
+            extract_dotnet_version(&prefix_and_suffix),
+            Ok("v1.2.3-beta.1".to_string())
+        );
+
+        let explicit_version = csproj(
+            "    <VersionPrefix>1.2.3</VersionPrefix>\n    <VersionSuffix>beta.1</VersionSuffix>\n    <Version>2.0.0</Version>\n",
+        );
+        assert_eq!(
+            extract_dotnet_version(&explicit_version),
+            Ok("v2.0.0".to_string())
+        );
+
+        // A suffix alone isn't a version
+        let suffix_only = csproj("    <VersionSuffix>beta.1</VersionSuffix>\n");
+        assert_eq!(
+            extract_dotnet_version(&suffix_only),
+            Err(PackageError::NoVersionField)
+        );
+
+        // Neither is a prefix without any digits
+        let placeholder_prefix = csproj(
+            "    <VersionPrefix>next</VersionPrefix>\n    <VersionSuffix>beta</VersionSuffix>\n",
+        );
+        assert_eq!(
+            extract_dotnet_version(&placeholder_prefix),
+            Err(PackageError::NoVersionField)
+        );
+    }
+
+    #[test]
+    fn test_extract_nuspec_version() {
+        let nuspec = "<?xml version=\"1.0\" encoding=\"utf-8\"?>
+<package xmlns=\"http://schemas.microsoft.com/packaging/2013/05/nuspec.xsd\">
+  <metadata minClientVersion=\"3.3.0\">
+    <id>Starship</id>
+    <dependencies>
+      <group targetFramework=\"net8.0\">
+        <dependency id=\"Newtonsoft.Json\" version=\"13.0.1\" />
+      </group>
+    </dependencies>
+    <version>0.1.0</version>
+    <authors>Starship</authors>
+  </metadata>
+</package>";
+
+        let expected_version = Some("v0.1.0".to_string());
+        assert_eq!(extract_nuspec_version(nuspec).ok(), expected_version);
+
+        let nuspec_without_version = "<package><metadata><id>Starship</id></metadata></package>";
+
+        let expected_version = None;
+        assert_eq!(
+            extract_nuspec_version(nuspec_without_version).ok(),
+            expected_version
+        );
+    }
+
+    #[test]
+    fn folder_with_csproj_and_global_json() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Starship.csproj", STARSHIP_CSPROJ)?;
+        fill_config(
+            &dir,
+            "global.json",
+            &json::json!({ "sdk": { "version": "8.0.100", "rollForward": "latestFeature" } })
+                .to_string(),
+        )?;
+
+        assert_eq!(
+            get_tool_version(ManifestKind::Dotnet, dir.path()),
+            Some(".NET SDK v8.0.100".to_string())
+        );
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                show_tool_version = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208)
+                .bold()
+                .paint("📦 v0.1.0 (.NET SDK v8.0.100)")
+        ));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn folder_with_symlinked_manifest() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let real_dir = dir.path().join("real");
+        let project_dir = dir.path().join("project");
+        fs::create_dir_all(&real_dir)?;
+        fs::create_dir_all(&project_dir)?;
+        fs::write(real_dir.join("Cargo.toml"), CARGO_TOML)?;
+        std::os::unix::fs::symlink(real_dir.join("Cargo.toml"), project_dir.join("Cargo.toml"))?;
+
+        let actual =
+            get_package_version(&project_dir, &PackageConfig::new()).map(|package| package.version);
+        assert_eq!(actual, Ok("v0.1.0".to_string()));
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn folder_in_symlink_cycle() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let loop_dir = dir.path().join("a");
+        fs::create_dir_all(&loop_dir)?;
+        std::os::unix::fs::symlink(&loop_dir, loop_dir.join("loop"))?;
+
+        // `a/loop/loop` and `a/loop` are both `a`, which is only searched once
+        let context = Context::new_with_dir(
+            clap::ArgMatches::default(),
+            loop_dir.join("loop").join("loop"),
+        );
+        let actual = find_package_version(&context, &PackageConfig::new()).unwrap();
+        assert_eq!(actual.version, "v0.1.0");
+        assert_eq!(actual.file, dir.path().join("Cargo.toml"));
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_release_candidate_and_version_case() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "Cargo.toml",
+            "[package]\nname = \"starship\"\nversion = \"0.1.0-RC1\"\n",
+        )?;
+
+        for &(case, expected_version) in &[
+            ("preserve", "v0.1.0-RC1"),
+            ("lower", "v0.1.0-rc1"),
+            ("upper", "v0.1.0-RC1"),
+        ] {
+            let actual = ModuleRenderer::new("package")
+                .path(dir.path())
+                .config(toml::toml! {
+                    [package]
+                    version_case = case
+                })
+                .collect();
+            let expected = Some(format!(
+                "is {} ",
+                Color::Fixed(208)
+                    .bold()
+                    .paint(format!("📦 {}", expected_version))
+            ));
+            assert_eq!(expected, actual);
+        }
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_homebrew_tap() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let formula_dir = dir.path().join("Formula");
+        fs::create_dir_all(&formula_dir)?;
+        fs::write(
+            formula_dir.join("starship.rb"),
+            "class Starship < Formula\n  version \"0.1.0\"\nend\n",
+        )?;
+
+        let actual = get_package_version(dir.path(), &PackageConfig::new());
+        assert_eq!(
+            actual,
+            Ok(PackageVersion {
+                version: "v0.1.0".to_string(),
+                manifest: ManifestKind::Homebrew,
+                name: None,
+                dir: dir.path().to_path_buf(),
+                file: formula_dir.join("starship.rb"),
+            })
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_is_ignored_path() {
+        let ignore_paths = ["**/node_modules/**", "/vendor/*"];
+
+        assert!(is_ignored_path(
+            Path::new("/home/user/project/node_modules/starship"),
+            &ignore_paths
+        ));
+        assert!(is_ignored_path(
+            Path::new("/vendor/starship"),
+            &ignore_paths
+        ));
+        assert!(!is_ignored_path(
+            Path::new("/home/user/project/src"),
+            &ignore_paths
+        ));
+        assert!(!is_ignored_path(Path::new("/home/user/project"), &[]));
+    }
+
+    #[test]
+    fn folder_in_ignored_path() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let vendor_dir = dir.path().join("vendor").join("starship");
+        fs::create_dir_all(&vendor_dir)?;
+        fs::write(vendor_dir.join("Cargo.toml"), CARGO_TOML)?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let config = toml::toml! {
+            [package]
+            ignore_paths = ["**/vendor/**"]
+        };
+
+        let actual = ModuleRenderer::new("package")
+            .path(&vendor_dir)
+            .config(config.clone())
+            .collect();
+        assert_eq!(None, actual);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config)
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    const MIX_UMBRELLA: &str = "defmodule Starship.Umbrella.MixProject do
+  use Mix.Project
+
+  def project do
+    [
+      apps_path: \"apps\",
+      version: \"0.1.0\",
+      start_permanent: Mix.env() == :prod,
+      deps: deps()
+    ]
+  end
+
+  defp deps do
+    []
+  end
+end";
+
+    #[test]
+    fn folder_with_mix_umbrella() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "mix.exs", MIX_UMBRELLA)?;
+        fs::create_dir_all(dir.path().join("apps"))?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path().join("apps"))
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_mix_umbrella_child() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "mix.exs", MIX_UMBRELLA)?;
+        let app_dir = dir.path().join("apps").join("starship");
+        fs::create_dir_all(app_dir.join("lib"))?;
+        fs::write(
+            app_dir.join("mix.exs"),
+            "defmodule Starship.MixProject do
+  use Mix.Project
+
+  def project do
+    [
+      app: :starship,
+      version: \"0.2.0\",
+      build_path: \"../../_build\",
+      deps_path: \"../../deps\",
+      in_umbrella: true
+    ]
+  end
+end",
+        )?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(app_dir.join("lib"))
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.2.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_cargo_toml_and_registry_link() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                link_to_registry = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208)
+                .bold()
+                .paint("📦 \u{1b}]8;;https://crates.io/crates/starship\u{7}v0.1.0\u{1b}]8;;\u{7}")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_composer_json_and_registry_link() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "composer.json",
+            r#"{"name": "starship/prompt", "version": "0.1.0"}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                link_to_registry = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint(
+                "📦 \u{1b}]8;;https://packagist.org/packages/starship/prompt\u{7}v0.1.0\u{1b}]8;;\u{7}"
+            )
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_cargo_toml_and_registry_override() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                link_to_registry = true
+                registry_overrides = { cargo = "https://crates.example.com" }
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint(
+                "📦 \u{1b}]8;;https://crates.example.com/crates/starship\u{7}v0.1.0\u{1b}]8;;\u{7}"
+            )
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_cargo_toml_and_cached_update() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        // The cache is shared by the tests, so each one checks its own registry
+        let dir_name = dir.path().file_name().unwrap_or_default().to_string_lossy();
+        let registry = format!("https://crates.example.com/{}", dir_name);
+        let url = format!("{}/api/v1/crates/starship", registry);
+
+        // A fresh check is cached, so no update check is started in the background
+        let cache_dir = package_cache_dir().ok_or("no cache directory")?;
+        let cache_file = cache_dir.join(cache_file_name(&url));
+        write_cached_latest_version(&cache_dir, &cache_file, &url, SystemTime::now(), "v0.2.0")?;
+        let cache_contents = fs::read_to_string(&cache_file)?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                check_updates = true
+                registry_overrides = { cargo = registry }
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0 ⇡v0.2.0")
+        ));
+        assert_eq!(expected, actual);
+        assert_eq!(fs::read_to_string(&cache_file)?, cache_contents);
+        fs::remove_file(cache_file)?;
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn folder_with_unknown_registry_and_registry_link() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "build.gradle", "version '0.1.0'")?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                link_to_registry = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_uv_workspace_member() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root_pyproject = toml::toml! {
+            [project]
+            name = "starship"
+            version = "0.1.0"
+
+            [tool.uv.workspace]
+            members = ["packages/*"]
+            exclude = ["packages/legacy"]
+        }
+        .to_string();
+        fill_config(&dir, "pyproject.toml", &root_pyproject)?;
+
+        let member_pyproject = toml::toml! {
+            [project]
+            name = "starship-core"
+        }
+        .to_string();
+        for member in &["core", "legacy"] {
+            let member_dir = dir.path().join("packages").join(member);
+            fs::create_dir_all(&member_dir)?;
+            fs::write(member_dir.join("pyproject.toml"), &member_pyproject)?;
+        }
+
+        let actual = get_package_version(
+            &dir.path().join("packages").join("core"),
+            &PackageConfig::new(),
+        )
+        .map(|package| package.version);
+        assert_eq!(actual, Ok("v0.1.0".to_string()));
+
+        let actual = get_package_version(
+            &dir.path().join("packages").join("legacy"),
+            &PackageConfig::new(),
+        )
+        .map(|package| package.version);
+        assert_eq!(actual, Err(PackageError::NoVersionField));
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_extra_yaml_manifest() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "blueprint.yaml",
+            "kind: service\nversion: \"0.1.0\"\n",
+        )?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        assert_eq!(None, actual);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                extra_yaml_manifests = ["service.yaml", "blueprint.yaml"]
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_unpublishable_crate() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "Cargo.toml",
+            &format!("{}publish = false\n", CARGO_TOML),
+        )?;
+        let config = toml::toml! {
+            [package]
+            hide_unpublishable = true
+        };
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config.clone())
+            .collect();
+        assert_eq!(None, actual);
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config)
+            .collect();
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_custom_prefix_and_suffix() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                prefix = "at "
+                suffix = " | "
+            })
+            .collect();
+        let expected = Some(format!(
+            "at {} | ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                prefix = ""
+                suffix = ""
+            })
+            .collect();
+        let expected = Some(Color::Fixed(208).bold().paint("📦 v0.1.0").to_string());
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_maturin_project() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let pyproject = toml::toml! {
+            [build-system]
+            requires = ["maturin>=1.0,<2.0"]
+            build-backend = "maturin"
+
+            [project]
+            name = "starship"
+            version = "0.2.0"
+
+            [tool.maturin]
+            features = ["pyo3/extension-module"]
+        }
+        .to_string();
+        fill_config(&dir, "pyproject.toml", &pyproject)?;
+
+        let actual = get_package_version(dir.path(), &PackageConfig::new());
+        assert_eq!(
+            actual.map(|package| (package.version, package.manifest)),
+            Ok(("v0.2.0".to_string(), ManifestKind::PyProject))
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_maturin_project_with_dynamic_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let pyproject = toml::toml! {
+            [build-system]
+            requires = ["maturin>=1.0,<2.0"]
+            build-backend = "maturin"
+
+            [project]
+            name = "starship"
+            dynamic = ["version"]
+
+            [tool.maturin]
+            features = ["pyo3/extension-module"]
+        }
+        .to_string();
+        fill_config(&dir, "pyproject.toml", &pyproject)?;
+
+        let actual = get_package_version(dir.path(), &PackageConfig::new());
+        assert_eq!(
+            actual.map(|package| (package.version, package.manifest)),
+            Ok(("v0.1.0".to_string(), ManifestKind::Cargo))
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_custom_json_pointer_manifest() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let project_json = json::json!({
+            "name": "starship",
+            "targets": { "build": { "options": { "app.version": "0.1.0" } } }
+        });
+        fill_config(&dir, "project.json", &project_json.to_string())?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                detect_json = [{ file = "project.json", key = "/targets/build/options/app.version" }]
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_custom_extension_manifest() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "starship.myproj",
+            "<MyProject>\n  <Name>starship</Name>\n  <Version>0.1.0</Version>\n</MyProject>\n",
+        )?;
+        let config = toml::toml! {
+            [package]
+            detect_extensions = [
+                { extension = "myproj", parser = "regex", key = "<Version>(.+)</Version>" },
+                { extension = ".other", parser = "toml" },
+            ]
+        };
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+
+        // Without the `myproj` file, the next registered extension is looked for
+        fs::remove_file(dir.path().join("starship.myproj"))?;
+        fill_config(&dir, "starship.other", "version = \"0.2.0\"\n")?;
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config)
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.2.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_custom_toml_and_json_manifests() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "foo.json",
+            "{ \"build\": { \"version\": \"0.2.0\" } }",
+        )?;
+        let config = toml::toml! {
+            [package]
+            detect_toml = [{ file = "foo.toml", key = "pkg.version" }]
+            detect_json = [{ file = "foo.json", key = "build.version" }]
+        };
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.2.0")
+        ));
+        assert_eq!(expected, actual);
+
+        fill_config(&dir, "foo.toml", "[pkg]\nversion = \"0.1.0\"\n")?;
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config)
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_extract_default_custom_version() {
+        for (manifest, expected) in [
+            ("version = \"0.1.0\"\n", Ok("v0.1.0".to_string())),
+            ("[package]\nversion = \"0.1.0\"\n", Ok("v0.1.0".to_string())),
+            (
+                "version = \"0.2.0\"\n[package]\nversion = \"0.1.0\"\n",
+                Ok("v0.2.0".to_string()),
+            ),
+            (
+                "[tool]\nversion = \"0.1.0\"\n",
+                Err(PackageError::NoVersionField),
+            ),
+            ("version = ", Err(PackageError::ParseFailed)),
+        ] {
+            assert_eq!(
+                extract_default_custom_version(CustomFormat::Toml, manifest),
+                expected,
+                "{}",
+                manifest
+            );
+        }
+    }
+
+    #[test]
+    fn folder_with_custom_toml_manifest_without_key() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = toml::toml! {
+            [package]
+            detect_toml = ["tool.toml", { file = "other.toml" }]
+        };
+
+        fill_config(&dir, "other.toml", "[package]\nversion = \"0.2.0\"\n")?;
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.2.0")
+        ));
+        assert_eq!(expected, actual);
+
+        fill_config(&dir, "tool.toml", "version = \"0.1.0\"\n")?;
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config)
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_broken_manifest_and_error_symbol() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", "[package\nversion = \"0.1.0\"\n")?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                error_symbol = "✗"
+            })
+            .collect();
+        assert_eq!(None, actual);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                show_parse_errors = true
+                error_symbol = "✗"
+            })
+            .collect();
+        let expected = Some(format!("is {} ", Color::Fixed(208).bold().paint("📦 ✗")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_broken_manifest_and_error_style() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", "[package\nversion = \"0.1.0\"\n")?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                show_parse_errors = true
+                error_symbol = "✗"
+                error_style = "red"
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {}{} ",
+            Color::Fixed(208).bold().paint("📦 "),
+            Color::Red.paint("✗")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_parse_latest_version() {
+        let crates_io = r#"{"crate": {"name": "starship", "max_stable_version": "0.2.0"}}"#;
+        assert_eq!(
+            ManifestKind::Cargo.parse_latest_version(crates_io),
+            Some("v0.2.0".to_string())
+        );
+        let npm = r#"{"name": "starship", "version": "0.2.0"}"#;
+        assert_eq!(
+            ManifestKind::Npm.parse_latest_version(npm),
+            Some("v0.2.0".to_string())
+        );
+        assert_eq!(ManifestKind::Npm.parse_latest_version("Not Found"), None);
+        assert_eq!(ManifestKind::Gradle.parse_latest_version(npm), None);
+    }
+
+    #[test]
+    fn test_is_version_behind() {
+        for &(version, latest, expected) in &[
+            ("v0.1.0", "v0.2.0", true),
+            ("v1.9.0", "v1.10.0", true),
+            ("v0.2.0", "v0.2.0", false),
+            ("v0.3.0", "v0.2.0", false),
+            ("v0.2.0-rc.1", "v0.2.0", true),
+            ("v0.2.0", "v0.2.0-rc.1", false),
+            ("v0.2.0-rc.1", "v0.2.0-rc.2", true),
+            ("v0.2.0-rc.10", "v0.2.0-rc.2", false),
+            ("v0.2.0-1", "v0.2.0-rc.1", true),
+            ("v0.2", "v0.2.0", false),
+            ("v0.2.0", "v0.2", false),
+            ("v0.2", "v0.2.1", true),
+            ("v0.2.0+build.1", "v0.2.0", false),
+        ] {
+            assert_eq!(is_version_behind(version, latest), expected, "{}", version);
+        }
+    }
+
+    #[test]
+    fn test_get_latest_version() -> io::Result<()> {
+        let cache_dir = tempfile::tempdir()?;
+        let hour = Duration::from_secs(60 * 60);
+        let ttl = 6 * hour;
+        let now = SystemTime::now();
+        let url = "https://registry.npmjs.org/@scope/starship/latest";
+        let refreshes = Cell::new(0);
+        let refresh = || refreshes.set(refreshes.get() + 1);
+
+        // The first check runs in the background, there's nothing to show until it's done
+        let latest = get_latest_version(url, cache_dir.path(), ttl, now, refresh);
+        assert_eq!(latest, None);
+        assert_eq!(refreshes.get(), 1);
+
+        // A check that failed, e.g. offline, isn't retried until it is older than the TTL
+        let latest = get_latest_version(url, cache_dir.path(), ttl, now + hour, refresh);
+        assert_eq!(latest, None);
+        assert_eq!(refreshes.get(), 1);
+
+        let registry = |version: &'static str| {
+            move |fetched_url: &str| {
+                assert_eq!(fetched_url, url);
+                Some(format!(r#"{{"version": "{}"}}"#, version))
+            }
+        };
+        let fetched = update_latest_version(
+            ManifestKind::Npm,
+            url,
+            cache_dir.path(),
+            now + hour,
+            registry("0.2.0"),
+        );
+        assert_eq!(fetched, Some("v0.2.0".to_string()));
+
+        // The fetched release is used until it is older than the TTL
+        let cached = get_latest_version(url, cache_dir.path(), ttl, now + 5 * hour, refresh);
+        assert_eq!(cached, Some("v0.2.0".to_string()));
+        assert_eq!(refreshes.get(), 1);
+
+        // Once it is, it is still shown while checking again
+        let stale = get_latest_version(url, cache_dir.path(), ttl, now + 8 * hour, refresh);
+        assert_eq!(stale, Some("v0.2.0".to_string()));
+        assert_eq!(refreshes.get(), 2);
+        let cached = get_latest_version(url, cache_dir.path(), ttl, now + 9 * hour, refresh);
+        assert_eq!(cached, Some("v0.2.0".to_string()));
+        assert_eq!(refreshes.get(), 2);
+
+        // A failed fetch keeps the previous release
+        let failed = update_latest_version(ManifestKind::Npm, url, cache_dir.path(), now, |_| None);
+        assert_eq!(failed, None);
+        let cached = get_latest_version(url, cache_dir.path(), ttl, now + 9 * hour, refresh);
+        assert_eq!(cached, Some("v0.2.0".to_string()));
+
+        // Another registry doesn't share the cached release
+        let other_registry = get_latest_version(
+            "https://npm.example.com/@scope/starship/latest",
+            cache_dir.path(),
+            ttl,
+            now,
+            refresh,
+        );
+        assert_eq!(other_registry, None);
+        assert_eq!(refreshes.get(), 3);
+        cache_dir.close()
+    }
+
+    #[test]
+    fn test_read_cached_latest_version() -> io::Result<()> {
+        let cache_dir = tempfile::tempdir()?;
+        let cache_file = cache_dir.path().join("latest");
+        let url = "https://crates.io/api/v1/crates/star-ship";
+
+        fs::write(&cache_file, format!("{}\n1700000000000\nv0.2.0", url))?;
+        assert_eq!(
+            read_cached_latest_version(&cache_file, url),
+            Some((
+                SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+                "v0.2.0".to_string()
+            ))
+        );
+
+        // Releases cached for another URL sharing the cache file are refreshed
+        let other_url = "https://crates.io/api/v1/crates/star_ship";
+        assert_eq!(cache_file_name(url), cache_file_name(other_url));
+        assert_eq!(read_cached_latest_version(&cache_file, other_url), None);
+
+        // Caches written before the URL and the check time were stored are refreshed
+        fs::write(&cache_file, "1700000000000\nv0.2.0")?;
+        assert_eq!(read_cached_latest_version(&cache_file, url), None);
+        fs::write(&cache_file, "v0.2.0")?;
+        assert_eq!(read_cached_latest_version(&cache_file, url), None);
+        cache_dir.close()
+    }
+
+    #[test]
+    fn folder_with_versionless_manifest_and_show_when_no_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", "[package]\nname = \"starship\"\n")?;
+        let config = toml::toml! {
             [package]
-            name = "starship"
-        }
-        .to_string();
+            show_when_no_version = true
+        };
 
-        let expected_version = None;
-        assert_eq!(
-            extract_cargo_version(&cargo_without_version),
-            expected_version
-        );
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        assert_eq!(None, actual);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config)
+            .collect();
+        let expected = Some(format!("is {} ", Color::Fixed(208).bold().paint("📦")));
+        assert_eq!(expected, actual);
+        dir.close()
     }
 
     #[test]
-    fn test_extract_package_version() {
-        let package_with_version = json::json!({
-            "name": "spacefish",
-            "version": "0.1.0"
-        })
-        .to_string();
+    fn folder_without_manifest_and_show_when_no_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
 
-        let expected_version = Some("v0.1.0".to_string());
-        assert_eq!(
-            extract_package_version(&package_with_version),
-            expected_version
-        );
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                show_when_no_version = true
+            })
+            .collect();
+        assert_eq!(None, actual);
+        dir.close()
     }
 
     #[test]
-    fn test_extract_package_version_without_version() {
-        let package_without_version = json::json!({
-            "name": "spacefish"
-        })
-        .to_string();
+    fn test_explain_package_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let formula_dir = dir.path().join("Formula");
+        fs::create_dir(&formula_dir)?;
+        fs::write(
+            formula_dir.join("starship.rb"),
+            "class Starship < Formula\n  version \"0.1.0\"\nend\n",
+        )?;
 
-        let expected_version = None;
+        let mut config = PackageConfig::new();
+        let package = get_package_version(dir.path(), &config).unwrap();
+        assert_eq!(package.file, formula_dir.join("starship.rb"));
         assert_eq!(
-            extract_package_version(&package_without_version),
-            expected_version
+            explain_package_version(&package, "v0.1.0", "v0.1.0", &config),
+            format!(
+                "The package version of the current directory's project: \
+                 v0.1.0 read from {} (Homebrew manifest, brew ecosystem)",
+                formula_dir.join("starship.rb").display()
+            )
+        );
+
+        config.trim_prefix = Some("v");
+        config.version_case = VersionCase::Upper;
+        assert!(explain_package_version(&package, "v0.1.0", "0.1.0", &config).ends_with(
+            "(Homebrew manifest, brew ecosystem), shown as 0.1.0 after trim_prefix = \"v\", version_case = Upper"
+        ));
+
+        let calver = PackageVersion {
+            version: "v2024.03.1".to_string(),
+            ..package
+        };
+        let config = PackageConfig {
+            hide_calver_prefix: true,
+            ..PackageConfig::new()
+        };
+        assert!(
+            explain_package_version(&calver, "v2024.03.1", "2024.03.1", &config).ends_with(
+                "(Homebrew manifest, brew ecosystem), shown as 2024.03.1 after hide_calver_prefix"
+            )
         );
+
+        let placeholder = PackageVersion {
+            version: "v0.0.0".to_string(),
+            ..calver
+        };
+        let config = PackageConfig {
+            resolve_git_version: true,
+            trim_prefix: Some("v"),
+            ..PackageConfig::new()
+        };
+        assert!(
+            explain_package_version(&placeholder, "v0.2.0", "0.2.0", &config).ends_with(
+                "(Homebrew manifest, brew ecosystem), shown as 0.2.0 after \
+                 resolve_git_version (v0.2.0 from git describe), trim_prefix = \"v\""
+            )
+        );
+        dir.close()
     }
 
     #[test]
-    fn test_extract_package_version_with_null_version() {
-        let package_with_null_version = json::json!({
-            "name": "spacefish",
-            "version": null
-        })
-        .to_string();
+    fn test_explain_package_version_of_module() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), dir.path());
+        context.config = StarshipConfig { config: None };
 
-        let expected_version = None;
-        assert_eq!(
-            extract_package_version(&package_with_null_version),
-            expected_version
+        let rendered = module(&context).unwrap();
+        let description = rendered.get_description();
+        assert!(description.contains("v0.1.0"), "{}", description);
+        assert!(
+            description.contains(&dir.path().join("Cargo.toml").display().to_string()),
+            "{}",
+            description
+        );
+        assert!(
+            description.contains("(Cargo manifest, cargo ecosystem)"),
+            "{}",
+            description
         );
+
+        fill_config(&dir, "VERSION", "0.2.0")?;
+        fs::remove_file(dir.path().join("Cargo.toml"))?;
+        let rendered = module(&context).unwrap();
+        assert!(rendered.get_description().ends_with(&format!(
+            "read from {} (VersionFile manifest)",
+            dir.path().join("VERSION").display()
+        )));
+        dir.close()
     }
 
     #[test]
-    fn test_extract_package_version_with_null_string_version() {
-        let package_with_null_string_version = json::json!({
-            "name": "spacefish",
-            "version": "null"
-        })
-        .to_string();
+    fn folder_with_cargo_toml_and_trailing_space() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
 
-        let expected_version = None;
-        assert_eq!(
-            extract_package_version(&package_with_null_string_version),
-            expected_version
-        );
+        for &(trailing_space, suffix) in &[(true, " "), (false, "")] {
+            let actual = ModuleRenderer::new("package")
+                .path(dir.path())
+                .config(toml::toml! {
+                    [package]
+                    trailing_space = trailing_space
+                })
+                .collect();
+            let expected = Some(format!(
+                "is {}{}",
+                Color::Fixed(208).bold().paint("📦 v0.1.0"),
+                suffix
+            ));
+            assert_eq!(expected, actual);
+        }
+        dir.close()
     }
 
     #[test]
-    fn test_extract_private_package_version() {
-        let private_package = json::json!({
-            "name": "spacefish",
-            "version": "0.1.0",
-            "private": true
-        })
-        .to_string();
+    fn folder_with_fallback_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = toml::toml! {
+            [package]
+            fallback_version = "1.0.0"
+        };
 
-        let expected_version = None;
-        assert_eq!(extract_package_version(&private_package), expected_version);
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v1.0.0")
+        ));
+        assert_eq!(expected, actual);
+
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(config)
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
     }
 
     #[test]
-    fn test_extract_poetry_version() {
-        let poetry_with_version = toml::toml! {
-            [tool.poetry]
-            name = "starship"
-            version = "0.1.0"
+    fn folder_below_nested_package_with_context_root() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        git2::Repository::init(dir.path())?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let package_dir = dir.path().join("web");
+        let deep_dir = package_dir.join("src").join("components");
+        fs::create_dir_all(&deep_dir)?;
+        fs::write(package_dir.join("package.json"), r#"{"version": "0.2.0"}"#)?;
+
+        for &(context_root, expected_version) in
+            &[("current_dir", "v0.2.0"), ("git_root", "v0.1.0")]
+        {
+            let actual = ModuleRenderer::new("package")
+                .path(&deep_dir)
+                .config(toml::toml! {
+                    [package]
+                    context_root = context_root
+                })
+                .collect();
+            let expected = Some(format!(
+                "is {} ",
+                Color::Fixed(208)
+                    .bold()
+                    .paint(format!("📦 {}", expected_version))
+            ));
+            assert_eq!(expected, actual, "{}", context_root);
         }
-        .to_string();
+        dir.close()?;
+        Ok(())
+    }
 
-        let expected_version = Some("v0.1.0".to_string());
+    #[test]
+    fn folder_outside_repository_with_git_root_context() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let sub_dir = dir.path().join("src");
+        fs::create_dir(&sub_dir)?;
+
+        // Without a repository, the search starts from the current directory as usual
+        let actual = ModuleRenderer::new("package")
+            .path(&sub_dir)
+            .config(toml::toml! {
+                [package]
+                context_root = "git_root"
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_dir_files_are_listed_once() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "starship.gemspec", "")?;
+        fill_config(&dir, "starship.opam", "")?;
+        fs::create_dir(dir.path().join("Formula.rb"))?;
+
+        let files = DirFiles::new(dir.path());
         assert_eq!(
-            extract_poetry_version(&poetry_with_version),
-            expected_version
+            find_manifest_with_extension(&files, "opam"),
+            Ok(Some(dir.path().join("starship.opam")))
         );
+        // Directories aren't manifests, even with a manifest's extension
+        assert_eq!(find_manifest_with_extension(&files, "rb"), Ok(None));
 
-        let poetry_without_version = toml::toml! {
-            [tool.poetry]
-            name = "starship"
-        }
-        .to_string();
-
-        let expected_version = None;
+        // Files added later aren't seen by the same probe
+        fill_config(&dir, "starship.cabal", "")?;
+        assert_eq!(find_manifest_with_extension(&files, "cabal"), Ok(None));
         assert_eq!(
-            extract_poetry_version(&poetry_without_version),
-            expected_version
+            find_manifest_with_extension(&DirFiles::new(dir.path()), "cabal"),
+            Ok(Some(dir.path().join("starship.cabal")))
         );
+        dir.close()
     }
 
     #[test]
-    fn test_extract_gradle_version() {
-        let gradle_single_quotes = "plugins {
-    id 'java'
-    id 'test.plugin' version '0.2.0'
-}
-version '0.1.0'
-java {
-    sourceCompatibility = JavaVersion.VERSION_1_8
-    targetCompatibility = JavaVersion.VERSION_1_8
-}";
-
-        let expected_version = Some("v0.1.0".to_string());
+    fn test_find_manifest_matching() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
         assert_eq!(
-            extract_gradle_version(&gradle_single_quotes),
-            expected_version
+            find_manifest_matching(&DirFiles::new(dir.path()), "Package*.swift"),
+            Ok(None)
         );
 
-        let gradle_double_quotes = "plugins {
-    id 'java'
-    id 'test.plugin' version '0.2.0'
-}
-version \"0.1.0\"
-java {
-    sourceCompatibility = JavaVersion.VERSION_1_8
-    targetCompatibility = JavaVersion.VERSION_1_8
-}";
-
-        let expected_version = Some("v0.1.0".to_string());
+        fill_config(
+            &dir,
+            "Package@swift-5.9.swift",
+            "// swift-tools-version:5.9",
+        )?;
+        fill_config(&dir, "Package.resolved", "{}")?;
         assert_eq!(
-            extract_gradle_version(&gradle_double_quotes),
-            expected_version
+            find_manifest_matching(&DirFiles::new(dir.path()), "Package*.swift"),
+            Ok(Some(dir.path().join("Package@swift-5.9.swift")))
         );
 
-        let gradle_release_candidate = "plugins {
-    id 'java'
-    id 'test.plugin' version '0.2.0'
-}
-version '0.1.0-rc1'
-java {
-    sourceCompatibility = JavaVersion.VERSION_1_8
-    targetCompatibility = JavaVersion.VERSION_1_8
-}";
-
-        let expected_version = Some("v0.1.0-rc1".to_string());
+        fill_config(&dir, "Package.swift", "// swift-tools-version:5.5")?;
         assert_eq!(
-            extract_gradle_version(&gradle_release_candidate),
-            expected_version
+            find_manifest_matching(&DirFiles::new(dir.path()), "Package*.swift"),
+            Ok(Some(dir.path().join("Package.swift")))
         );
+        dir.close()
+    }
 
-        let gradle_without_version = "plugins {
-    id 'java'
-    id 'test.plugin' version '0.2.0'
-}
-java {
-    sourceCompatibility = JavaVersion.VERSION_1_8
-    targetCompatibility = JavaVersion.VERSION_1_8
-}";
+    #[test]
+    fn folder_with_version_specific_swift_manifest() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "Package@swift-5.9.swift",
+            "// swift-tools-version:5.9\nimport PackageDescription\n",
+        )?;
 
-        let expected_version = None;
+        // The manifest is found, but the version has to come from git
         assert_eq!(
-            extract_gradle_version(&gradle_without_version),
-            expected_version
+            get_package_version(dir.path(), &PackageConfig::new()),
+            Err(PackageError::NoVersionField)
         );
+
+        let repository = git2::Repository::init(dir.path())?;
+        let mut index = repository.index()?;
+        index.add_path(Path::new("Package@swift-5.9.swift"))?;
+        index.write()?;
+        let tree = repository.find_tree(index.write_tree()?)?;
+        let signature = git2::Signature::now("Starship", "starship@example.com")?;
+        let commit = repository.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Initial commit",
+            &tree,
+            &[],
+        )?;
+        repository.tag_lightweight("v0.1.0", &repository.find_object(commit, None)?, false)?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                resolve_git_version = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()?;
+        Ok(())
     }
 
     #[test]
-    fn test_extract_mix_version() {
-        let mix_complete = "defmodule MyApp.MixProject do
-  use Mix.Project
+    fn folder_with_scoped_package_and_display_name() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "package.json",
+            r#"{"name": "@my-org/some-really-long-package", "version": "0.1.0"}"#,
+        )?;
 
-  def project do
-    [
-      app: :my_app,
-      version: \"1.2.3\",
-      elixir: \"~> 1.10\",
-      start_permanent: Mix.env() == :prod,
-      deps: deps()
-    ]
-  end
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                display_name = true
+                truncate_name_length = 20
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208)
+                .bold()
+                .paint("📦 some-really-long-pa… v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
 
-  # Run \"mix help compile.app\" to learn about applications.
-  def application do
-    [extra_applications: [:logger]]
-  end
+    #[test]
+    fn folder_with_composer_json_and_display_name() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "composer.json",
+            r#"{"name": "starship/prompt", "version": "0.1.0"}"#,
+        )?;
 
-  # Run \"mix help deps\" to learn about dependencies.
-  defp deps do
-    []
-  end
-end";
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                display_name = true
+                truncate_name_length = 20
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 prompt v0.1.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
 
-        let expected_version = Some("v1.2.3".to_string());
-        assert_eq!(extract_mix_version(&mix_complete), expected_version);
+    #[test]
+    fn test_map_version() {
+        let mut version_map = HashMap::new();
+        version_map.insert("0.0.0-dev".to_string(), "edge");
+        version_map.insert("v1.0.0".to_string(), "stable");
 
-        let mix_partial_oneline = "  def project, do: [app: :my_app,version: \"3.2.1\"]";
+        assert_eq!(map_version("v0.0.0-dev", &version_map), Some("edge"));
+        assert_eq!(map_version("v1.0.0", &version_map), Some("stable"));
+        assert_eq!(map_version("v0.1.0", &version_map), None);
+        assert_eq!(map_version("v0.0.0-dev.1", &version_map), None);
+    }
 
-        let expected_version = Some("v3.2.1".to_string());
-        assert_eq!(extract_mix_version(&mix_partial_oneline), expected_version);
+    #[test]
+    fn test_version_map() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(
+            &dir,
+            "Cargo.toml",
+            &CARGO_TOML.replace("0.1.0", "0.0.0-dev"),
+        )?;
 
-        let mix_partial_prerelease = "  def project do
-    [
-      app: :my_app,
-      version: \"1.0.0-alpha.3\"
-    ]
-  end";
+        let render = |dir: &Path| {
+            ModuleRenderer::new("package")
+                .path(dir)
+                .config(toml::toml! {
+                    [package]
+                    version_case = "upper"
+                    [package.version_map]
+                    "0.0.0-dev" = "edge"
+                })
+                .collect()
+        };
+        let expected = Some(format!("is {} ", Color::Fixed(208).bold().paint("📦 edge")));
+        assert_eq!(render(dir.path()), expected);
 
-        let expected_version = Some("v1.0.0-alpha.3".to_string());
-        assert_eq!(
-            extract_mix_version(&mix_partial_prerelease),
-            expected_version
-        );
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(render(dir.path()), expected);
+        dir.close()
+    }
 
-        let mix_partial_prerelease_and_build_info = "  def project do
-    [
-      app: :my_app,
-      version: \"0.9.9-dev+20130417140000.amd64\"
-    ]
-  end";
+    #[test]
+    fn test_version_mismatch_with_git_version() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = setuptools_scm_repo()?;
+        fill_config(
+            &dir,
+            "Cargo.toml",
+            "[package]\nname = \"starship\"\nversion = \"0.2.0\"\n",
+        )?;
+        let config = PackageConfig {
+            resolve_git_version: true,
+            ..PackageConfig::new()
+        };
 
-        let expected_version = Some("v0.9.9-dev+20130417140000.amd64".to_string());
-        assert_eq!(
-            extract_mix_version(&mix_partial_prerelease_and_build_info),
-            expected_version
-        );
+        // The `v0.1.0` tag of the setuptools-scm project isn't a version its manifest declares
+        let package = get_package_version(dir.path(), &config).unwrap();
+        assert_eq!(package.manifest, ManifestKind::Cargo);
+        assert!(find_version_mismatches(&package, &config).is_empty());
+        dir.close()?;
+        Ok(())
     }
 
     #[test]
-    fn test_extract_composer_version() {
-        let composer_with_version = json::json!({
-            "name": "spacefish",
-            "version": "0.1.0"
-        })
-        .to_string();
+    fn test_version_mismatch() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        fill_config(&dir, "package.json", r#"{ "version": "0.1.0" }"#)?;
 
-        let expected_version = Some("v0.1.0".to_string());
-        assert_eq!(
-            extract_composer_version(&composer_with_version),
-            expected_version
-        );
+        let render = |dir: &Path| {
+            ModuleRenderer::new("package")
+                .path(dir)
+                .config(toml::toml! {
+                    [package]
+                    warn_on_version_mismatch = true
+                })
+                .collect()
+        };
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(render(dir.path()), expected);
 
-        let composer_without_version = json::json!({
-            "name": "spacefish"
-        })
-        .to_string();
+        fill_config(&dir, "package.json", r#"{ "version": "0.2.0" }"#)?;
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0!")
+        ));
+        assert_eq!(render(dir.path()), expected);
 
-        let expected_version = None;
+        let package = get_package_version(dir.path(), &PackageConfig::new()).unwrap();
         assert_eq!(
-            extract_composer_version(&composer_without_version),
-            expected_version
+            find_version_mismatches(&package, &PackageConfig::new()),
+            vec![format!(
+                "v0.2.0 in {}",
+                dir.path().join("package.json").display()
+            )]
         );
+
+        // Mismatches are only looked for when asked to
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+        dir.close()
     }
 
     #[test]
-    fn test_extract_project_version() {
-        let project_with_version = toml::toml! {
-            name = "starship"
-            version = "0.1.0"
+    fn test_symbol_separator() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+
+        let actual = ModuleRenderer::new("package").path(dir.path()).collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                symbol = "📦"
+                symbol_separator = " | "
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 | v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn test_symbol_padding() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                symbol_prefix = "["
+                symbol_suffix = " "
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("[📦  v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                symbol = "🦀"
+                symbol_suffix = " "
+                symbol_separator = "| "
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("🦀 | v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn test_symbol_padding_without_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "package.json", "{ \"name\": \"starship\" }")?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                show_when_no_version = true
+                symbol_prefix = " "
+                symbol_suffix = " "
+            })
+            .collect();
+        let expected = Some(format!("is {} ", Color::Fixed(208).bold().paint(" 📦 ")));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn test_ecosystem_label() -> io::Result<()> {
+        let cases = [
+            ("Cargo.toml", CARGO_TOML, "cargo"),
+            ("package.json", r#"{ "version": "0.1.0" }"#, "npm"),
+            ("pyproject.toml", "[project]\nversion = \"0.1.0\"\n", "pypi"),
+            ("composer.json", r#"{ "version": "0.1.0" }"#, "composer"),
+            (
+                "Starship.csproj",
+                "<Project><PropertyGroup><Version>0.1.0</Version></PropertyGroup></Project>",
+                "nuget",
+            ),
+        ];
+        for (file_name, contents, label) in cases {
+            let dir = tempfile::tempdir()?;
+            fill_config(&dir, file_name, contents)?;
+
+            let actual = ModuleRenderer::new("package")
+                .path(dir.path())
+                .config(toml::toml! {
+                    [package]
+                    show_ecosystem_label = true
+                })
+                .collect();
+            let expected = Some(format!(
+                "is {} ",
+                Color::Fixed(208)
+                    .bold()
+                    .paint(format!("📦 {} v0.1.0", label))
+            ));
+            assert_eq!(actual, expected, "{}", file_name);
+            dir.close()?;
         }
-        .to_string();
 
-        let expected_version = Some("v0.1.0".to_string());
-        assert_eq!(
-            extract_project_version(&project_with_version),
-            expected_version
-        );
+        // Manifests configured by the user have no ecosystem
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "VERSION", "0.1.0\n")?;
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path())
+            .config(toml::toml! {
+                [package]
+                show_ecosystem_label = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
 
-        let project_without_version = toml::toml! {
-            [package]
-            name = "starship"
+    #[test]
+    fn test_is_unstable_version() {
+        for &(version, expected) in &[
+            ("v0.9.0", true),
+            ("v0.1.0-rc.1", true),
+            ("0.1", true),
+            ("v1.0.0", false),
+            ("v10.0.0", false),
+            ("2024.03", false),
+            ("edge", false),
+        ] {
+            assert_eq!(is_unstable_version(version), expected, "{}", version);
         }
-        .to_string();
+    }
 
-        let expected_version = None;
-        assert_eq!(
-            extract_project_version(&project_without_version),
-            expected_version
-        );
+    #[test]
+    fn test_unstable_style() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let render = |dir: &Path| {
+            ModuleRenderer::new("package")
+                .path(dir)
+                .config(toml::toml! {
+                    [package]
+                    unstable_style = "yellow"
+                })
+                .collect()
+        };
+
+        fill_config(&dir, "Cargo.toml", &CARGO_TOML.replace("0.1.0", "0.9.0"))?;
+        let expected = Some(format!(
+            "is {}{} ",
+            Color::Fixed(208).bold().paint("📦 "),
+            Color::Yellow.paint("v0.9.0")
+        ));
+        assert_eq!(render(dir.path()), expected);
+
+        fill_config(&dir, "Cargo.toml", &CARGO_TOML.replace("0.1.0", "1.0.0"))?;
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v1.0.0")
+        ));
+        assert_eq!(render(dir.path()), expected);
+        dir.close()
+    }
+
+    #[test]
+    fn test_version_lock_mismatch() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fill_config(&dir, "Cargo.toml", CARGO_TOML)?;
+        let render = |dir: &Path| {
+            ModuleRenderer::new("package")
+                .path(dir)
+                .config(toml::toml! {
+                    [package]
+                    warn_on_lock_mismatch = true
+                })
+                .collect()
+        };
+
+        // Without a lock, there is nothing to disagree with
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(render(dir.path()), expected);
+
+        fill_config(&dir, ".version-lock", "0.1.0\n")?;
+        assert_eq!(get_locked_version(dir.path()), Some("v0.1.0".to_string()));
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(render(dir.path()), expected);
+
+        fill_config(&dir, ".version-lock", "v0.2.0\n")?;
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0!")
+        ));
+        assert_eq!(render(dir.path()), expected);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_node_engine_and_show_tool_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let render = |dir: &Path| {
+            ModuleRenderer::new("package")
+                .path(dir)
+                .config(toml::toml! {
+                    [package]
+                    show_tool_version = true
+                })
+                .collect()
+        };
+
+        fill_config(
+            &dir,
+            "package.json",
+            &json::json!({ "version": "0.1.0", "engines": { "node": ">=18" } }).to_string(),
+        )?;
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0 (node >=18)")
+        ));
+        assert_eq!(render(dir.path()), expected);
+
+        fill_config(
+            &dir,
+            "package.json",
+            &json::json!({ "version": "0.1.0", "engines": { "npm": ">=9" } }).to_string(),
+        )?;
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(render(dir.path()), expected);
+        dir.close()
     }
 }
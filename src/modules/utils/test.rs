@@ -1,12 +1,51 @@
 use crate::config::StarshipConfig;
 use crate::context::{Context, Shell};
-use std::path::Path;
+use std::env;
+use std::path::{Path, PathBuf};
 
 /// Render a specific starship module by name
 pub fn render_module(module_name: &str, path: &Path) -> Option<String> {
-    let mut context = Context::new_with_dir(clap::ArgMatches::default(), path);
-    context.config = StarshipConfig { config: None };
-    context.shell = Shell::Unknown;
+    ModuleRenderer::new(module_name).path(path).collect()
+}
+
+/// Renders a single module against a directory and an optional configuration,
+/// e.g. `ModuleRenderer::new("package").path(dir.path()).collect()`
+pub struct ModuleRenderer<'a> {
+    name: &'a str,
+    path: PathBuf,
+    config: Option<toml::Value>,
+}
+
+impl<'a> ModuleRenderer<'a> {
+    /// Creates a renderer for the module with the given name
+    pub fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            path: env::temp_dir(),
+            config: None,
+        }
+    }
+
+    /// Sets the directory the module is rendered in
+    pub fn path<T: Into<PathBuf>>(mut self, path: T) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Sets the starship configuration, as if read from `starship.toml`
+    pub fn config(mut self, config: toml::Value) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Renders the module, returning `None` if it isn't shown
+    pub fn collect(self) -> Option<String> {
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), self.path);
+        context.config = StarshipConfig {
+            config: self.config,
+        };
+        context.shell = Shell::Unknown;
 
-    crate::print::get_module(module_name, context)
+        crate::print::get_module(self.name, context)
+    }
 }
@@ -95,7 +95,38 @@ Elixir 1.10 (compiled with Erlang/OTP 22)",
 pub fn wrap_colorseq_for_shell(ansi: String, shell: Shell) -> String {
     const ESCAPE_BEGIN: char = '\u{1b}';
     const ESCAPE_END: char = 'm';
-    wrap_seq_for_shell(ansi, shell, ESCAPE_BEGIN, ESCAPE_END)
+    const OSC_BEGIN: &str = "\u{1b}]";
+    const OSC_END: char = '\u{7}';
+
+    // OSC sequences (e.g. hyperlinks) end with BEL rather than `m`, which they may contain
+    let mut wrapped = String::with_capacity(ansi.len());
+    let mut rest = ansi.as_str();
+    while let Some(start) = rest.find(OSC_BEGIN) {
+        let end = match rest[start..].find(OSC_END) {
+            Some(length) => start + length + OSC_END.len_utf8(),
+            None => break,
+        };
+        wrapped.push_str(&wrap_seq_for_shell(
+            rest[..start].to_string(),
+            shell,
+            ESCAPE_BEGIN,
+            ESCAPE_END,
+        ));
+        wrapped.push_str(&wrap_seq_for_shell(
+            rest[start..end].to_string(),
+            shell,
+            ESCAPE_BEGIN,
+            OSC_END,
+        ));
+        rest = &rest[end..];
+    }
+    wrapped.push_str(&wrap_seq_for_shell(
+        rest.to_string(),
+        shell,
+        ESCAPE_BEGIN,
+        ESCAPE_END,
+    ));
+    wrapped
 }
 
 /// Many shells cannot deal with raw unprintable characters and miscompute the cursor position,
@@ -271,4 +302,21 @@ mod tests {
         assert_eq!(&bresult4, "herpaderp");
         assert_eq!(&bresult5, "");
     }
+
+    #[test]
+    fn test_hyperlink_sequence_wrappers() {
+        let link = "\x1b[1m\x1b]8;;https://www.npmjs.com\x07npm\x1b]8;;\x07\x1b[0m";
+
+        let zresult = wrap_colorseq_for_shell(link.to_string(), Shell::Zsh);
+        let bresult = wrap_colorseq_for_shell(link.to_string(), Shell::Bash);
+
+        assert_eq!(
+            &zresult,
+            "%{\x1b[1m%}%{\x1b]8;;https://www.npmjs.com\x07%}npm%{\x1b]8;;\x07%}%{\x1b[0m%}"
+        );
+        assert_eq!(
+            &bresult,
+            "\\[\x1b[1m\\]\\[\x1b]8;;https://www.npmjs.com\x07\\]npm\\[\x1b]8;;\x07\\]\\[\x1b[0m\\]"
+        );
+    }
 }